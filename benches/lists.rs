@@ -0,0 +1,106 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Compares reading and writing lists of primitives as lists, as `Vec<T>` and
+//! `[T; N]` do, with reading and writing them one value at a time.
+
+use cornflakes::{ByteOrder, ByteOrdered, ContextualReadable, Readable, Writable};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const LEN: usize = 64 * 1024;
+
+fn read_one_at_a_time<T: Readable>(mut reader: &[u8], len: usize) -> Vec<T> {
+	(0..len)
+		.map(|_| T::read_from(&mut reader).unwrap())
+		.collect()
+}
+
+fn write_one_at_a_time<T: Writable>(list: &[T], writer: &mut Vec<u8>) {
+	for value in list {
+		value.write_to(writer).unwrap();
+	}
+}
+
+fn read_lists(c: &mut Criterion) {
+	// Varied bytes, so that values read differently in each byte order.
+	let bytes: Vec<u8> = (0..LEN * 4).map(|i| i as u8).collect();
+	let mut group = c.benchmark_group("read");
+
+	group.bench_function(BenchmarkId::new("Vec<u8>", "list"), |b| {
+		b.iter(|| Vec::<u8>::read_with(&mut black_box(&bytes[..]), &LEN).unwrap());
+	});
+	group.bench_function(BenchmarkId::new("Vec<u8>", "one at a time"), |b| {
+		b.iter(|| read_one_at_a_time::<u8>(black_box(&bytes), LEN));
+	});
+
+	group.bench_function(BenchmarkId::new("Vec<u32>", "list"), |b| {
+		b.iter(|| Vec::<u32>::read_with(&mut black_box(&bytes[..]), &LEN).unwrap());
+	});
+	group.bench_function(BenchmarkId::new("Vec<u32>", "one at a time"), |b| {
+		b.iter(|| read_one_at_a_time::<u32>(black_box(&bytes), LEN));
+	});
+	group.bench_function(BenchmarkId::new("Vec<u32>", "little endian list"), |b| {
+		b.iter(|| {
+			let mut reader = ByteOrdered::new(black_box(&bytes[..]), ByteOrder::LittleEndian);
+			Vec::<u32>::read_with(&mut reader, &LEN).unwrap()
+		});
+	});
+
+	group.bench_function(BenchmarkId::new("[u32; 256]", "in place"), |b| {
+		b.iter(|| <[u32; 256]>::read_from(&mut black_box(&bytes[..])).unwrap());
+	});
+	group.bench_function(BenchmarkId::new("[u32; 256]", "one at a time"), |b| {
+		b.iter(|| read_one_at_a_time::<u32>(black_box(&bytes), 256));
+	});
+
+	group.finish();
+}
+
+fn write_lists(c: &mut Criterion) {
+	let bytes = vec![0xa5u8; LEN];
+	let words = vec![0xa5a5_a5a5u32; LEN];
+	let mut group = c.benchmark_group("write");
+
+	group.bench_function(BenchmarkId::new("Vec<u8>", "list"), |b| {
+		b.iter(|| {
+			let mut writer = Vec::with_capacity(LEN);
+			black_box(&bytes).write_to(&mut writer).unwrap();
+			writer
+		});
+	});
+	group.bench_function(BenchmarkId::new("Vec<u8>", "one at a time"), |b| {
+		b.iter(|| {
+			let mut writer = Vec::with_capacity(LEN);
+			write_one_at_a_time(black_box(&bytes), &mut writer);
+			writer
+		});
+	});
+
+	group.bench_function(BenchmarkId::new("Vec<u32>", "list"), |b| {
+		b.iter(|| {
+			let mut writer = Vec::with_capacity(LEN * 4);
+			black_box(&words).write_to(&mut writer).unwrap();
+			writer
+		});
+	});
+	group.bench_function(BenchmarkId::new("Vec<u32>", "one at a time"), |b| {
+		b.iter(|| {
+			let mut writer = Vec::with_capacity(LEN * 4);
+			write_one_at_a_time(black_box(&words), &mut writer);
+			writer
+		});
+	});
+	group.bench_function(BenchmarkId::new("Vec<u32>", "little endian list"), |b| {
+		b.iter(|| {
+			let mut writer = ByteOrdered::new(Vec::with_capacity(LEN * 4), ByteOrder::LittleEndian);
+			black_box(&words).write_to(&mut writer).unwrap();
+			writer
+		});
+	});
+
+	group.finish();
+}
+
+criterion_group!(benches, read_lists, write_lists);
+criterion_main!(benches);
@@ -0,0 +1,274 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use proc_macro2::{Literal, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+	parse::{Parse, ParseStream},
+	Attribute, DataEnum, DeriveInput, Expr, ExprLit, Field, Ident, Lit, LitInt, Token, Type,
+};
+
+/// The `#[context(name: Type)]` attribute on a type deriving
+/// `ContextualReadable`.
+///
+/// `name` is bound to a reference to the context so that fields' `#[context]`
+/// attributes can refer to it.
+pub struct ContainerContext {
+	pub name: Ident,
+	pub ty: Type,
+}
+
+impl Parse for ContainerContext {
+	fn parse(input: ParseStream) -> syn::Result<Self> {
+		let name = input.parse()?;
+		input.parse::<Token![:]>()?;
+		let ty = input.parse()?;
+
+		Ok(Self { name, ty })
+	}
+}
+
+/// Parses the `#[context(name: Type)]` attribute on a type deriving
+/// `ContextualReadable`.
+pub fn container_context(input: &DeriveInput) -> ContainerContext {
+	let attr = find_attribute(&input.attrs, "context").unwrap_or_else(|| {
+		panic!("ContextualReadable requires a `#[context(name: Type)]` attribute on the type")
+	});
+
+	attr.parse_args()
+		.unwrap_or_else(|error| panic!("invalid `#[context(name: Type)]` attribute: {error}"))
+}
+
+/// Parses the `#[context(expr)]` attribute on a field, if there is one.
+///
+/// If present, the field is read with `ContextualReadable` using the given
+/// expression as its context. The expression may refer to any fields read
+/// before this one.
+pub fn field_context(field: &Field) -> Option<Expr> {
+	find_attribute(&field.attrs, "context").map(|attr| {
+		attr.parse_args()
+			.unwrap_or_else(|error| panic!("invalid `#[context(expr)]` attribute: {error}"))
+	})
+}
+
+/// Finds the attribute called `name` in `attrs`, if there is one.
+fn find_attribute<'a>(attrs: &'a [Attribute], name: &str) -> Option<&'a Attribute> {
+	attrs.iter().find(|attr| attr.path().is_ident(name))
+}
+
+/// The `#[pad(...)]` attribute on a field, which adds padding after it.
+pub enum Pad {
+	/// `#[pad(bytes = n)]`: `n` bytes of padding.
+	Bytes(LitInt),
+	/// `#[pad(align = n)]`: pads the field to a multiple of `n` bytes.
+	Align(LitInt),
+}
+
+impl Parse for Pad {
+	fn parse(input: ParseStream) -> syn::Result<Self> {
+		let key: Ident = input.parse()?;
+		input.parse::<Token![=]>()?;
+		let value = input.parse()?;
+
+		match key.to_string().as_str() {
+			"bytes" => Ok(Self::Bytes(value)),
+			"align" => Ok(Self::Align(value)),
+
+			_ => Err(syn::Error::new(
+				key.span(),
+				"expected `bytes = n` or `align = n`",
+			)),
+		}
+	}
+}
+
+/// Parses the `#[pad(...)]` attribute on a field, if there is one.
+pub fn field_padding(field: &Field) -> Option<Pad> {
+	find_attribute(&field.attrs, "pad").map(|attr| {
+		attr.parse_args().unwrap_or_else(|error| {
+			panic!("invalid `#[pad(bytes = n)]` or `#[pad(align = n)]` attribute: {error}")
+		})
+	})
+}
+
+/// Returns an expression for the number of padding bytes after `field`, given
+/// an expression for the `size` of the field, if `field` has a `#[pad(...)]`
+/// attribute.
+pub fn padding(field: &Field, size: &TokenStream2) -> Option<TokenStream2> {
+	field_padding(field).map(|pad| match pad {
+		Pad::Bytes(bytes) => quote!(#bytes),
+		Pad::Align(align) => quote!(cornflakes::pad(#size, #align)),
+	})
+}
+
+/// Returns an expression for the size of `field` including its padding, given
+/// an expression for the `size` of the field without padding.
+pub fn padded_size(field: &Field, size: TokenStream2) -> TokenStream2 {
+	match padding(field, &size) {
+		Some(padding) => quote!((#size + #padding)),
+		None => size,
+	}
+}
+
+/// Parses the `#[static_size = n]` attribute on a type deriving
+/// `StaticDataSize`, if there is one.
+///
+/// If present, the type's computed static size is asserted to be `n` at
+/// compile time.
+pub fn static_size(attrs: &[Attribute]) -> Option<LitInt> {
+	find_attribute(attrs, "static_size").map(|attr| {
+		let value = &attr
+			.meta
+			.require_name_value()
+			.unwrap_or_else(|error| panic!("invalid `#[static_size = n]` attribute: {error}"))
+			.value;
+
+		match value {
+			Expr::Lit(ExprLit {
+				lit: Lit::Int(int), ..
+			}) => int.clone(),
+
+			_ => panic!("the size in `#[static_size = n]` must be an integer literal"),
+		}
+	})
+}
+
+/// Parses the `#[sentinel(value)]` attribute on a type or variant, if there is
+/// one.
+pub fn sentinel(attrs: &[Attribute]) -> Option<Expr> {
+	find_attribute(attrs, "sentinel").map(|attr| {
+		attr.parse_args()
+			.unwrap_or_else(|error| panic!("invalid `#[sentinel(value)]` attribute: {error}"))
+	})
+}
+
+/// The `#[bitmask(Type)]` or `#[bitmask(Type, strict)]` attribute on an enum
+/// deriving `Flag`.
+pub struct Bitmask {
+	pub ty: Ident,
+	pub strict: bool,
+}
+
+impl Parse for Bitmask {
+	fn parse(input: ParseStream) -> syn::Result<Self> {
+		let ty = input.parse()?;
+		let mut strict = false;
+
+		if input.parse::<Option<Token![,]>>()?.is_some() {
+			let option: Ident = input.parse()?;
+
+			if option != "strict" {
+				return Err(syn::Error::new(option.span(), "expected `strict`"));
+			}
+
+			strict = true;
+		}
+
+		Ok(Self { ty, strict })
+	}
+}
+
+/// Parses the `#[bitmask(Type)]` attribute on an enum deriving `Flag`.
+pub fn bitmask(attrs: &[Attribute]) -> Bitmask {
+	let attr = find_attribute(attrs, "bitmask")
+		.unwrap_or_else(|| panic!("Flag requires a `#[bitmask(Type)]` attribute on the type"));
+
+	attr.parse_args().unwrap_or_else(|error| {
+		panic!("invalid `#[bitmask(Type)]` or `#[bitmask(Type, strict)]` attribute: {error}")
+	})
+}
+
+/// Parses the `#[discriminant(Type)]` attribute on an enum, returning the type
+/// with which the enum's discriminant is read and written.
+///
+/// The discriminant is a `u8` if there is no `#[discriminant(Type)]`
+/// attribute.
+pub fn discriminant_type(attrs: &[Attribute]) -> Ident {
+	find_attribute(attrs, "discriminant").map_or_else(
+		|| format_ident!("u8"),
+		|attr| {
+			attr.parse_args().unwrap_or_else(|error| {
+				panic!("invalid `#[discriminant(Type)]` attribute: {error}")
+			})
+		},
+	)
+}
+
+/// Parses an integer literal used as the discriminant of `variant`.
+fn discriminant_value(variant: &Ident, expr: &Expr) -> u64 {
+	match expr {
+		Expr::Lit(ExprLit {
+			lit: Lit::Int(int), ..
+		}) => int
+			.base10_parse()
+			.unwrap_or_else(|error| panic!("invalid discriminant for `{variant}`: {error}")),
+
+		_ => panic!("the discriminant for `{variant}` must be an integer literal"),
+	}
+}
+
+/// Returns the discriminant of each variant of `data_enum` as a literal of the
+/// discriminant type `ty`.
+///
+/// A variant's discriminant is given either explicitly (`Variant = 33`) or with
+/// a `#[discriminant = 33]` attribute, which can be used on variants with
+/// fields. Otherwise, like in Rust, it is one more than the discriminant of the
+/// previous variant, starting from zero.
+pub fn discriminants(data_enum: &DataEnum, ty: &Ident) -> Vec<Literal> {
+	let max = match ty.to_string().as_str() {
+		"u8" => u64::from(u8::MAX),
+		"u16" => u64::from(u16::MAX),
+		"u32" => u64::from(u32::MAX),
+		"u64" => u64::MAX,
+		other => {
+			panic!("unsupported discriminant type `{other}`: expected `u8`, `u16`, `u32` or `u64`")
+		}
+	};
+
+	let mut next = Some(0);
+	let mut discriminants = Vec::new();
+
+	for variant in &data_enum.variants {
+		let ident = &variant.ident;
+
+		let attribute = find_attribute(&variant.attrs, "discriminant").map(|attr| {
+			&attr
+				.meta
+				.require_name_value()
+				.unwrap_or_else(|error| {
+					panic!("invalid `#[discriminant = value]` attribute on `{ident}`: {error}")
+				})
+				.value
+		});
+
+		let discriminant =
+			match (&variant.discriminant, attribute) {
+				(Some(_), Some(_)) => {
+					panic!("`{ident}` has both an explicit discriminant and a `#[discriminant]` attribute")
+				}
+
+				(Some((_, expr)), None) | (None, Some(expr)) => discriminant_value(ident, expr),
+				(None, None) => {
+					next.unwrap_or_else(|| panic!("the discriminant for `{ident}` overflows `u64`"))
+				}
+			};
+
+		assert!(
+			discriminant <= max,
+			"the discriminant {discriminant} of `{ident}` is too large for `{ty}`"
+		);
+		assert!(
+			!discriminants.contains(&discriminant),
+			"the discriminant {discriminant} of `{ident}` is already used"
+		);
+
+		next = discriminant.checked_add(1);
+		discriminants.push(discriminant);
+	}
+
+	discriminants
+		.into_iter()
+		.map(Literal::u64_unsuffixed)
+		.collect()
+}
@@ -0,0 +1,21 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use quote::format_ident;
+use syn::{Fields, Ident};
+
+/// Generates the names of the local variables that fields are read into or
+/// written from.
+///
+/// Named fields use variables of the same name, unnamed fields use `field0`,
+/// `field1`, etc.
+pub fn field_names(fields: &Fields) -> Vec<Ident> {
+	match fields {
+		Fields::Named(field) => field.named.iter().filter_map(|f| f.ident.clone()).collect(),
+		Fields::Unnamed(field) => (0..(field.unnamed.len()))
+			.map(|index| format_ident!("field{}", index))
+			.collect(),
+		Fields::Unit => Vec::new(),
+	}
+}
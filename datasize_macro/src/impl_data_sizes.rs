@@ -2,13 +2,19 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
+use crate::attributes::{discriminant_type, padded_size};
+use crate::sentinel::SentinelEnum;
 use proc_macro2::TokenStream as TokenStream2;
 use quote::{format_ident, quote};
-use syn::{Data, DataEnum, DataStruct, DeriveInput, Fields, Ident, Index, Type};
+use syn::{Data, DataEnum, DataStruct, DeriveInput, Field, Fields, Ident, Index};
 
 pub fn impl_datasize(input: &DeriveInput) -> TokenStream2 {
+	if let Some(sentinel_enum) = SentinelEnum::from_input(input) {
+		return sentinel_enum.data_size();
+	}
+
 	match &input.data {
-		Data::Enum(e) => impl_datasize_enum(e),
+		Data::Enum(e) => impl_datasize_enum(&discriminant_type(&input.attrs), e),
 		Data::Struct(s) => impl_datasize_struct(s),
 		Data::Union(_) => {
 			panic!("Unions are used for C bindings, you probably don't need this trait for it");
@@ -17,16 +23,41 @@ pub fn impl_datasize(input: &DeriveInput) -> TokenStream2 {
 }
 
 pub fn impl_static_data_size(input: &DeriveInput) -> TokenStream2 {
+	if let Some(sentinel_enum) = SentinelEnum::from_input(input) {
+		return sentinel_enum.static_data_size();
+	}
+
 	match &input.data {
-		Data::Enum(e) => impl_static_datasize_enum(e),
-		Data::Struct(s) => impl_static_datasize_struct(s),
+		Data::Enum(e) => impl_static_datasize_enum(&discriminant_type(&input.attrs), e),
+		Data::Struct(s) => impl_static_datasize_struct(s, static_field_size),
 		Data::Union(_) => {
 			panic!("Unions are used for C bindings, you probably don't need this trait for it");
 		}
 	}
 }
 
-fn impl_datasize_enum(data_enum: &DataEnum) -> TokenStream2 {
+/// Generates the body of `DataSize::static_data_size_hint`, which is the size
+/// of every value if every field's `static_data_size_hint` is `Some` (and, for
+/// enums, every variant is the same size), and `None` otherwise.
+pub fn impl_static_data_size_hint(input: &DeriveInput) -> TokenStream2 {
+	if let Some(sentinel_enum) = SentinelEnum::from_input(input) {
+		return sentinel_enum.static_data_size_hint();
+	}
+
+	let size = match &input.data {
+		Data::Enum(e) => impl_static_data_size_hint_enum(&discriminant_type(&input.attrs), e),
+		Data::Struct(s) => impl_static_datasize_struct(s, field_size_hint),
+		Data::Union(_) => {
+			panic!("Unions are used for C bindings, you probably don't need this trait for it");
+		}
+	};
+
+	quote!(Some(#size))
+}
+
+fn impl_datasize_enum(discriminant_type: &Ident, data_enum: &DataEnum) -> TokenStream2 {
+	let discriminant_size = quote!(<#discriminant_type as cornflakes::StaticDataSize>::SIZE);
+
 	let branches = data_enum
 		.variants
 		.iter()
@@ -40,14 +71,18 @@ fn impl_datasize_enum(data_enum: &DataEnum) -> TokenStream2 {
 						.iter()
 						.filter_map(|f| f.ident.as_ref())
 						.collect();
-					let types: Vec<&Type> = field
+					let sizes: Vec<TokenStream2> = field
 						.named
 						.iter()
-						.map(|f| &f.ty)
+						.zip(&names)
+						.map(|(f, name)| {
+							let ty = &f.ty;
+							padded_size(f, quote!(<#ty as cornflakes::DataSize>::data_size(&#name)))
+						})
 						.collect();
 
 					quote! {
-						Self::#ident {#(#names),*} => { 0usize #( + <#types as cornflakes::DataSize>::data_size(&#names))*},
+						Self::#ident {#(#names),*} => { #discriminant_size #( + #sizes)*},
 					}
 				}
 				Fields::Unnamed(field) => {
@@ -55,19 +90,24 @@ fn impl_datasize_enum(data_enum: &DataEnum) -> TokenStream2 {
 					let names: Vec<Ident> = (0..(field.unnamed.len()))
 						.map(|index| format_ident!("field{}", index))
 						.collect();
-					let types: Vec<&Type> = field
+					let sizes: Vec<TokenStream2> = field
 						.unnamed
 						.iter()
-						.map(|f| &f.ty)
+						.zip(&names)
+						.map(|(f, name)| {
+							let ty = &f.ty;
+							padded_size(f, quote!(<#ty as cornflakes::DataSize>::data_size(&#name)))
+						})
 						.collect();
 
 					quote! {
-						Self::#ident (#(#names),*) => { 0usize #( + <#types as cornflakes::DataSize>::data_size(&#names))*},
+						Self::#ident (#(#names),*) => { #discriminant_size #( + #sizes)*},
 					}
 				}
-				// If the variant is a unit variant, then the size is 1 (size of it's discriminant)
+				// If the variant is a unit variant, then the size is the size of it's discriminant
+				// Every other variant is the discriminant plus the size of its fields
 				Fields::Unit => quote! {
-					Self::#ident => 1,
+					Self::#ident => #discriminant_size,
 				},
 			}
 		})
@@ -92,29 +132,41 @@ fn impl_datasize_struct(data_struct: &DataStruct) -> TokenStream2 {
 				.iter()
 				.filter_map(|f| f.ident.as_ref())
 				.collect();
-			let types: Vec<&Type> = field
+			let sizes: Vec<TokenStream2> = field
 				.named
 				.iter()
-				.map(|f| &f.ty)
+				.zip(&names)
+				.map(|(f, name)| {
+					let ty = &f.ty;
+					padded_size(
+						f,
+						quote!(<#ty as cornflakes::DataSize>::data_size(&self.#name)),
+					)
+				})
 				.collect();
 
 			quote! {
-				0usize #(+ <#types as cornflakes::DataSize>::data_size(&self.#names))*
+				0usize #(+ #sizes)*
 			}
 		}
 		Fields::Unnamed(field) => {
 			// Set a name for all fields of an unnamed variant
-			let names: Vec<Index> = (0..(field.unnamed.len()))
-				.map(|index| Index::from(index))
-				.collect();
-			let types: Vec<&Type> = field
+			let names: Vec<Index> = (0..(field.unnamed.len())).map(Index::from).collect();
+			let sizes: Vec<TokenStream2> = field
 				.unnamed
 				.iter()
-				.map(|f| &f.ty)
+				.zip(&names)
+				.map(|(f, name)| {
+					let ty = &f.ty;
+					padded_size(
+						f,
+						quote!(<#ty as cornflakes::DataSize>::data_size(&self.#name)),
+					)
+				})
 				.collect();
 
 			quote! {
-				0usize #(+ <#types as cornflakes::DataSize>::data_size(&self.#names))*
+				0usize #(+ #sizes)*
 			}
 		}
 		// If the variant is a unit variant, then the size is 0
@@ -122,28 +174,81 @@ fn impl_datasize_struct(data_struct: &DataStruct) -> TokenStream2 {
 	}
 }
 
-fn impl_static_datasize_enum(data_enum: &DataEnum) -> TokenStream2 {
-	data_enum
+fn impl_static_datasize_enum(discriminant_type: &Ident, data_enum: &DataEnum) -> TokenStream2 {
+	let max = data_enum
 		.variants
 		.iter()
 		.map(|variant| {
-			// Retrieve types for all fields
-			let types: Vec<&Type> = variant.fields.iter().map(|f| &f.ty).collect();
+			// Retrieve sizes for all fields
+			let sizes: Vec<TokenStream2> = variant.fields.iter().map(static_field_size).collect();
 			quote! {
-				0usize #( + <#types as cornflakes::StaticDataSize>::static_data_size())*
+				0usize #( + #sizes)*
 			}
 		})
-		// Use the maximum size among all variants
-		.fold(
-			quote!(0usize),
-			|tokens, branch| quote!(std::cmp::max(#tokens, #branch)),
-		)
+		// Use the maximum size among all variants (`std::cmp::max` can't be
+		// used in `const` contexts)
+		.fold(quote!(0usize), |tokens, branch| {
+			quote! {
+				{
+					let (a, b): (usize, usize) = (#tokens, #branch);
+					if a > b { a } else { b }
+				}
+			}
+		});
+
+	// Add the size of the discriminant
+	quote!(<#discriminant_type as cornflakes::StaticDataSize>::SIZE + #max)
+}
+
+/// Generates the static size hint of an enum, returning `None` early unless
+/// every variant is the same size: otherwise, the size of a value depends on
+/// its variant.
+fn impl_static_data_size_hint_enum(
+	discriminant_type: &Ident,
+	data_enum: &DataEnum,
+) -> TokenStream2 {
+	let sizes = data_enum.variants.iter().map(|variant| {
+		let sizes: Vec<TokenStream2> = variant.fields.iter().map(field_size_hint).collect();
+		quote!(0usize #( + #sizes)*)
+	});
+
+	quote! {
+		{
+			let __sizes: &[usize] = &[#(#sizes),*];
+
+			match __sizes.first() {
+				Some(__size) if __sizes.iter().all(|size| size == __size) => {
+					<#discriminant_type as cornflakes::StaticDataSize>::SIZE + __size
+				}
+				_ => return None,
+			}
+		}
+	}
 }
 
-fn impl_static_datasize_struct(data_struct: &DataStruct) -> TokenStream2 {
-	// Retrieve types of all fields
-	let types: Vec<&Type> = data_struct.fields.iter().map(|field| &field.ty).collect();
+fn impl_static_datasize_struct(
+	data_struct: &DataStruct,
+	field_size: fn(&Field) -> TokenStream2,
+) -> TokenStream2 {
+	// Retrieve sizes of all fields
+	let sizes: Vec<TokenStream2> = data_struct.fields.iter().map(field_size).collect();
+
+	// We add the `SIZE` of each of the types
+	quote! ( 0usize #(+ #sizes)*)
+}
+
+/// Returns the static size of `field`, including any padding.
+fn static_field_size(field: &Field) -> TokenStream2 {
+	let ty = &field.ty;
+	padded_size(field, quote!(<#ty as cornflakes::StaticDataSize>::SIZE))
+}
 
-	// We call `static_data_size()` on each of the names
-	quote! ( 0usize #(+ <#types as cornflakes::StaticDataSize>::static_data_size())*)
+/// Returns the static size hint of `field`, including any padding, returning
+/// `None` early if the field has no static size.
+fn field_size_hint(field: &Field) -> TokenStream2 {
+	let ty = &field.ty;
+	padded_size(
+		field,
+		quote!(<#ty as cornflakes::DataSize>::static_data_size_hint()?),
+	)
 }
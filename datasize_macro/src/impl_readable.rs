@@ -0,0 +1,163 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use crate::attributes::{discriminant_type, discriminants, field_context, padded_size, padding};
+use crate::fields::field_names;
+use crate::sentinel::SentinelEnum;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Data, DataEnum, DataStruct, DeriveInput, Fields, Ident};
+
+pub fn impl_readable(input: &DeriveInput) -> TokenStream2 {
+	if let Some(sentinel_enum) = SentinelEnum::from_input(input) {
+		return sentinel_enum.readable();
+	}
+
+	let root = input.ident.to_string();
+
+	let inner = match &input.data {
+		Data::Enum(e) => impl_readable_enum(&root, &discriminant_type(&input.attrs), e),
+		Data::Struct(s) => impl_readable_struct(&root, s),
+		Data::Union(_) => {
+			panic!("Unions are used for C bindings, you probably don't need this trait for it");
+		}
+	};
+
+	// The number of bytes remaining at the start is recorded so that the
+	// offset of a field can be added to any error that occurs reading it.
+	quote! {
+		let __start = __reader.remaining();
+
+		#inner
+	}
+}
+
+/// Generates the statements reading each field in declaration order, the
+/// expression constructing `path` from those fields, and the expression
+/// summing the sizes of those fields.
+///
+/// Errors reading a field are given the field's path within `root`.
+fn read_fields(
+	root: &str,
+	path: &TokenStream2,
+	fields: &Fields,
+) -> (TokenStream2, TokenStream2, TokenStream2) {
+	let names = field_names(fields);
+	let reads = fields
+		.iter()
+		.zip(&names)
+		.enumerate()
+		.map(|(index, (field, name))| {
+			let ty = &field.ty;
+			let field_name = field
+				.ident
+				.as_ref()
+				.map_or_else(|| index.to_string(), ToString::to_string);
+
+			let read = match field_context(field) {
+				Some(context) => quote! {
+					<#ty as cornflakes::ContextualReadable>::read_with(__reader, &(#context))
+				},
+				None => quote! {
+					<#ty as cornflakes::Readable>::read_from(__reader)
+				},
+			};
+
+			// Padding after the field is skipped.
+			let padding = padding(
+				field,
+				&quote!(<#ty as cornflakes::DataSize>::data_size(&#name)),
+			)
+			.map(|padding| {
+				quote! {
+					let __padding = #padding;
+					cornflakes::ensure_remaining(__reader, __padding)
+						.map_err(|error| error.in_field(#root, #field_name, __offset))?;
+					__reader.advance(__padding);
+				}
+			});
+
+			quote! {
+				let __offset = __start - __reader.remaining();
+				let #name = #read
+					.map_err(|error| error.in_field(#root, #field_name, __offset))?;
+				#padding
+			}
+		})
+		.fold(TokenStream2::new(), |tokens, read| quote! (#tokens #read));
+	let constructor = match fields {
+		Fields::Named(_) => quote!(#path { #(#names),* }),
+		Fields::Unnamed(_) => quote!(#path ( #(#names),* )),
+		Fields::Unit => quote!(#path),
+	};
+	let sizes: Vec<TokenStream2> = fields
+		.iter()
+		.zip(&names)
+		.map(|(field, name)| {
+			let ty = &field.ty;
+			padded_size(
+				field,
+				quote!(<#ty as cornflakes::DataSize>::data_size(&#name)),
+			)
+		})
+		.collect();
+	let size = quote! {
+		0usize #(+ #sizes)*
+	};
+
+	(reads, constructor, size)
+}
+
+fn impl_readable_enum(root: &str, discriminant_type: &Ident, data_enum: &DataEnum) -> TokenStream2 {
+	let branches = data_enum
+		.variants
+		.iter()
+		.zip(discriminants(data_enum, discriminant_type))
+		.map(|(variant, discriminant)| {
+			let ident = &variant.ident;
+			let (reads, constructor, size) = read_fields(
+				&format!("{root}::{ident}"),
+				&quote!(Self::#ident),
+				&variant.fields,
+			);
+
+			// If `Self` has a static size, smaller variants are padded up to
+			// that size, so that padding is skipped.
+			quote! {
+				#discriminant => {
+					#reads
+
+					let __size = <#discriminant_type as cornflakes::StaticDataSize>::SIZE + #size;
+					let __value = #constructor;
+
+					let __padding = <Self as cornflakes::DataSize>::data_size(&__value) - __size;
+					cornflakes::ensure_remaining(__reader, __padding)?;
+					__reader.advance(__padding);
+
+					Ok(__value)
+				}
+			}
+		})
+		.fold(
+			TokenStream2::new(),
+			|tokens, branch| quote! (#tokens #branch),
+		);
+
+	quote! {
+		match <#discriminant_type as cornflakes::Readable>::read_from(__reader)? {
+			#branches
+			other => Err(cornflakes::ReadError::UnrecognizedDiscriminant(other.into())),
+		}
+	}
+}
+
+fn impl_readable_struct(root: &str, data_struct: &DataStruct) -> TokenStream2 {
+	let (reads, constructor, _) = read_fields(root, &quote!(Self), &data_struct.fields);
+
+	quote! {
+		#reads
+
+		Ok(#constructor)
+	}
+}
@@ -0,0 +1,119 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use crate::attributes::{discriminant_type, discriminants, padded_size, padding};
+use crate::fields::field_names;
+use crate::sentinel::SentinelEnum;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Data, DataEnum, DataStruct, DeriveInput, Fields, Ident};
+
+pub fn impl_writable(input: &DeriveInput) -> TokenStream2 {
+	if let Some(sentinel_enum) = SentinelEnum::from_input(input) {
+		return sentinel_enum.writable();
+	}
+
+	match &input.data {
+		Data::Enum(e) => impl_writable_enum(&discriminant_type(&input.attrs), e),
+		Data::Struct(s) => impl_writable_struct(s),
+		Data::Union(_) => {
+			panic!("Unions are used for C bindings, you probably don't need this trait for it");
+		}
+	}
+}
+
+/// Generates a pattern destructuring `path` into its fields, the statements
+/// writing each of those fields in declaration order, and the expression
+/// summing the sizes of those fields.
+fn write_fields(
+	path: &TokenStream2,
+	fields: &Fields,
+) -> (TokenStream2, TokenStream2, TokenStream2) {
+	let names = field_names(fields);
+	let pattern = match fields {
+		Fields::Named(_) => quote!(#path { #(#names),* }),
+		Fields::Unnamed(_) => quote!(#path ( #(#names),* )),
+		Fields::Unit => quote!(#path),
+	};
+
+	let writes = fields
+		.iter()
+		.zip(&names)
+		.map(|(field, name)| {
+			let ty = &field.ty;
+			let size = quote!(<#ty as cornflakes::DataSize>::data_size(#name));
+
+			// Padding after the field is written as zeroes.
+			let padding =
+				padding(field, &size).map(|padding| quote!(__writer.put_bytes(0, #padding);));
+
+			quote! {
+				<#ty as cornflakes::Writable>::write_to(#name, __writer)?;
+				#padding
+			}
+		})
+		.fold(TokenStream2::new(), |tokens, write| quote! (#tokens #write));
+	let sizes: Vec<TokenStream2> = fields
+		.iter()
+		.zip(&names)
+		.map(|(field, name)| {
+			let ty = &field.ty;
+			padded_size(
+				field,
+				quote!(<#ty as cornflakes::DataSize>::data_size(#name)),
+			)
+		})
+		.collect();
+	let size = quote! {
+		0usize #(+ #sizes)*
+	};
+
+	(pattern, writes, size)
+}
+
+fn impl_writable_enum(discriminant_type: &Ident, data_enum: &DataEnum) -> TokenStream2 {
+	let branches = data_enum
+		.variants
+		.iter()
+		.zip(discriminants(data_enum, discriminant_type))
+		.map(|(variant, discriminant)| {
+			let ident = &variant.ident;
+			let (pattern, writes, size) = write_fields(&quote!(Self::#ident), &variant.fields);
+
+			// If `Self` has a static size, smaller variants are padded up to
+			// that size so that exactly `data_size()` bytes are written.
+			quote! {
+				#pattern => {
+					<#discriminant_type as cornflakes::Writable>::write_to(&#discriminant, __writer)?;
+					#writes
+
+					let __size = <#discriminant_type as cornflakes::StaticDataSize>::SIZE + #size;
+					__writer.put_bytes(0, <Self as cornflakes::DataSize>::data_size(self) - __size);
+				}
+			}
+		})
+		.fold(
+			TokenStream2::new(),
+			|tokens, branch| quote! (#tokens #branch),
+		);
+
+	quote! {
+		match self {
+			#branches
+		}
+
+		Ok(())
+	}
+}
+
+fn impl_writable_struct(data_struct: &DataStruct) -> TokenStream2 {
+	let (pattern, writes, _) = write_fields(&quote!(Self), &data_struct.fields);
+
+	quote! {
+		let #pattern = self;
+		#writes
+
+		Ok(())
+	}
+}
@@ -2,20 +2,32 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
-use impl_data_sizes::{impl_datasize, impl_static_data_size};
+use attributes::{bitmask, container_context, sentinel, static_size};
+use impl_data_sizes::{impl_datasize, impl_static_data_size, impl_static_data_size_hint};
+use impl_readable::impl_readable;
+use impl_writable::impl_writable;
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{
-	parse_macro_input, parse_quote, DeriveInput,
-};
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Fields};
 
+mod attributes;
+mod fields;
 mod impl_data_sizes;
+mod impl_readable;
+mod impl_writable;
+mod sentinel;
 
-#[proc_macro_derive(DataSize)]
+/// Derives `DataSize`.
+///
+/// The size of an enum is the size of its discriminant plus the size of the
+/// fields of the value's variant. To pad every variant to the size of the
+/// largest, derive `StaticDataSize` instead.
+#[proc_macro_derive(DataSize, attributes(discriminant, pad, sentinel))]
 pub fn derive_data_size(item: TokenStream) -> TokenStream {
 	let mut input = parse_macro_input!(item as DeriveInput);
 	let ident = &input.ident;
 	let inner = impl_datasize(&input);
+	let hint = impl_static_data_size_hint(&input);
 
 	input.generics.type_params_mut().for_each(|param| {
 		param.bounds.push(parse_quote!(cornflakes::DataSize));
@@ -24,19 +36,55 @@ pub fn derive_data_size(item: TokenStream) -> TokenStream {
 
 	let output = quote! {
 		impl #impl_generics cornflakes::DataSize for #ident #type_generics #where_clause {
-			default fn data_size(&self) -> usize {
+			fn data_size(&self) -> usize {
 				#inner
 			}
+
+			fn static_data_size_hint() -> Option<usize> {
+				#hint
+			}
 		}
 	};
 	output.into()
 }
 
-#[proc_macro_derive(StaticDataSize)]
+/// Derives `StaticDataSize`, and `DataSize` with every value being that size,
+/// so `DataSize` must not be derived too.
+///
+/// The size of an enum is the size of its discriminant plus the size of its
+/// largest variant: every variant is padded to that size.
+///
+/// A `#[static_size = n]` attribute on the type asserts that its size is `n`
+/// bytes: compilation fails if the computed size differs.
+#[proc_macro_derive(StaticDataSize, attributes(discriminant, pad, sentinel, static_size))]
 pub fn derive_static_data_size(item: TokenStream) -> TokenStream {
 	let mut input = parse_macro_input!(item as DeriveInput);
 	let ident = &input.ident;
-	let inner = impl_static_data_size(&input);
+	let mut inner = impl_static_data_size(&input);
+
+	// Generic types can't be named in a free `const` item, so the assertion is
+	// only evaluated once their `SIZE` is used.
+	let mut assertion = None;
+
+	if let Some(expected) = static_size(&input.attrs) {
+		let message = format!(
+			"the static size of `{ident}` does not match its `#[static_size = {expected}]` attribute"
+		);
+
+		inner = quote! {
+			{
+				let size: usize = #inner;
+				assert!(size == #expected, #message);
+				size
+			}
+		};
+
+		if input.generics.params.is_empty() {
+			assertion = Some(quote! {
+				const _: usize = <#ident as cornflakes::StaticDataSize>::SIZE;
+			});
+		}
+	}
 
 	input.generics.type_params_mut().for_each(|param| {
 		param.bounds.push(parse_quote!(cornflakes::StaticDataSize));
@@ -45,24 +93,284 @@ pub fn derive_static_data_size(item: TokenStream) -> TokenStream {
 
 	let output = quote! {
 		impl #impl_generics cornflakes::StaticDataSize for #ident #type_generics #where_clause {
-			fn static_data_size() -> usize {
+			const SIZE: usize = #inner;
+		}
+
+		impl #impl_generics cornflakes::DataSize for #ident #type_generics #where_clause {
+			fn data_size(&self) -> usize {
+				<Self as cornflakes::StaticDataSize>::SIZE
+			}
+
+			fn static_data_size_hint() -> Option<usize> {
+				Some(<Self as cornflakes::StaticDataSize>::SIZE)
+			}
+		}
+
+		#assertion
+	};
+	output.into()
+}
+
+/// Derives `Readable`, reading each field in declaration order.
+///
+/// A field with a `#[context(expr)]` attribute is read with
+/// `ContextualReadable`, using `expr` as its context. `expr` may refer to
+/// fields read before it.
+///
+/// A field with a `#[pad(bytes = n)]` attribute is followed by `n` bytes of
+/// padding, and a field with a `#[pad(align = n)]` attribute is followed by
+/// padding up to a multiple of `n` bytes. This is the same for every derive.
+///
+/// Enums first read their discriminant, which is a `u8` unless the enum has a
+/// `#[discriminant(Type)]` attribute, then the fields of the matching variant.
+/// Variants' discriminants may be given explicitly (`Variant = 33`) or with a
+/// `#[discriminant = 33]` attribute.
+///
+/// Enums whose unit variants have `#[sentinel(value)]` attributes are instead
+/// read as a single value: if it equals a variant's sentinel, that variant is
+/// returned, and otherwise the value is wrapped in the enum's only variant with
+/// a field. This is the same for every derive.
+#[proc_macro_derive(Readable, attributes(context, discriminant, pad, sentinel))]
+pub fn derive_readable(item: TokenStream) -> TokenStream {
+	let mut input = parse_macro_input!(item as DeriveInput);
+	let ident = &input.ident;
+	let inner = impl_readable(&input);
+
+	input.generics.type_params_mut().for_each(|param| {
+		param.bounds.push(parse_quote!(cornflakes::Readable));
+	});
+	let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+	let output = quote! {
+		impl #impl_generics cornflakes::Readable for #ident #type_generics #where_clause {
+			fn read_from(__reader: &mut impl cornflakes::OrderedBuf) -> cornflakes::ReadResult<Self> {
 				#inner
 			}
 		}
 	};
+	output.into()
+}
+
+/// Derives `ContextualReadable` in the same way as `Readable`.
+///
+/// The type requires a `#[context(name: Type)]` attribute: `Type` is the
+/// `Context` type, and `name` is bound to a reference to the context for use
+/// in fields' `#[context(expr)]` attributes.
+#[proc_macro_derive(ContextualReadable, attributes(context, discriminant, pad, sentinel))]
+pub fn derive_contextual_readable(item: TokenStream) -> TokenStream {
+	let mut input = parse_macro_input!(item as DeriveInput);
+	let ident = &input.ident;
+	let inner = impl_readable(&input);
+	let context = container_context(&input);
+	let (name, ty) = (&context.name, &context.ty);
 
-	// For specialization to work, we need an additionnal trait bound for Datasize
-	// that we couldn't add before
 	input.generics.type_params_mut().for_each(|param| {
-		param.bounds.push(parse_quote!(cornflakes::DataSize));
+		param.bounds.push(parse_quote!(cornflakes::Readable));
+	});
+	let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+	let output = quote! {
+		impl #impl_generics cornflakes::ContextualReadable for #ident #type_generics #where_clause {
+			type Context = #ty;
+
+			fn read_with(
+				__reader: &mut impl cornflakes::OrderedBuf,
+				#name: &Self::Context,
+			) -> cornflakes::ReadResult<Self> {
+				#inner
+			}
+		}
+	};
+	output.into()
+}
+
+/// Derives `Writable`, writing each field in declaration order.
+///
+/// Enums first write their discriminant, which is a `u8` unless the enum has a
+/// `#[discriminant(Type)]` attribute, then the fields of the variant.
+/// Variants' discriminants may be given explicitly (`Variant = 33`) or with a
+/// `#[discriminant = 33]` attribute.
+#[proc_macro_derive(Writable, attributes(discriminant, pad, sentinel))]
+pub fn derive_writable(item: TokenStream) -> TokenStream {
+	let mut input = parse_macro_input!(item as DeriveInput);
+	let ident = &input.ident;
+	let inner = impl_writable(&input);
+
+	input.generics.type_params_mut().for_each(|param| {
+		param.bounds.push(parse_quote!(cornflakes::Writable));
 	});
 	let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
 
 	let output = quote! {
-		#output
+		impl #impl_generics cornflakes::Writable for #ident #type_generics #where_clause {
+			fn write_to(&self, __writer: &mut impl cornflakes::OrderedBufMut) -> cornflakes::WriteResult {
+				cornflakes::ensure_capacity(
+					__writer,
+					<Self as cornflakes::DataSize>::data_size(self),
+				)?;
+
+				#inner
+			}
+		}
+	};
+	output.into()
+}
+
+/// Derives `Sentinel` for a struct with a single field, where the sentinel is
+/// given by a `#[sentinel(value)]` attribute on the struct:
+/// ```ignore
+/// #[derive(Sentinel)]
+/// #[sentinel(0x8000)]
+/// struct Modifiers(u16);
+/// ```
+#[proc_macro_derive(Sentinel, attributes(sentinel))]
+pub fn derive_sentinel(item: TokenStream) -> TokenStream {
+	let input = parse_macro_input!(item as DeriveInput);
+	let ident = &input.ident;
+	let value = sentinel(&input.attrs).unwrap_or_else(|| {
+		panic!("Sentinel requires a `#[sentinel(value)]` attribute on the type")
+	});
+
+	let sentinel = match &input.data {
+		Data::Struct(data_struct) if data_struct.fields.len() == 1 => match &data_struct.fields {
+			Fields::Named(fields) => {
+				let name = &fields.named[0].ident;
+				quote!(Self { #name: #value })
+			}
+			_ => quote!(Self(#value)),
+		},
+
+		_ => panic!("Sentinel can only be derived for structs with a single field"),
+	};
+
+	let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+	let output = quote! {
+		impl #impl_generics cornflakes::Sentinel for #ident #type_generics #where_clause {
+			const SENTINEL: Self = #sentinel;
+		}
+	};
+	output.into()
+}
+
+/// Derives `Wrapper` for a tuple struct with a single field, such as
+/// `struct Window(u32)`.
+///
+/// `DataSize`, `StaticDataSize`, `Readable` and `Writable` are also derived,
+/// and are the same as those of the wrapped type, so they must not be derived
+/// separately.
+#[proc_macro_derive(Wrapper)]
+pub fn derive_wrapper(item: TokenStream) -> TokenStream {
+	let input = parse_macro_input!(item as DeriveInput);
+	let ident = &input.ident;
+
+	let ty = match &input.data {
+		Data::Struct(data_struct) => match &data_struct.fields {
+			Fields::Unnamed(fields) if fields.unnamed.len() == 1 => &fields.unnamed[0].ty,
+			_ => panic!("Wrapper can only be derived for tuple structs with a single field"),
+		},
+		_ => panic!("Wrapper can only be derived for tuple structs with a single field"),
+	};
+
+	let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+	let output = quote! {
+		impl #impl_generics cornflakes::Wrapper for #ident #type_generics #where_clause {
+			type WrappedType = #ty;
+
+			fn wrap(val: Self::WrappedType) -> Self {
+				Self(val)
+			}
+
+			fn unwrap(&self) -> &Self::WrappedType {
+				&self.0
+			}
+		}
+
 		impl #impl_generics cornflakes::DataSize for #ident #type_generics #where_clause {
 			fn data_size(&self) -> usize {
-				<Self as cornflakes::StaticDataSize>::static_data_size()
+				<#ty as cornflakes::StaticDataSize>::SIZE
+			}
+
+			fn static_data_size_hint() -> Option<usize> {
+				Some(<#ty as cornflakes::StaticDataSize>::SIZE)
+			}
+		}
+
+		impl #impl_generics cornflakes::StaticDataSize for #ident #type_generics #where_clause {
+			const SIZE: usize = <#ty as cornflakes::StaticDataSize>::SIZE;
+		}
+
+		impl #impl_generics cornflakes::Readable for #ident #type_generics #where_clause {
+			fn read_from(__reader: &mut impl cornflakes::OrderedBuf) -> cornflakes::ReadResult<Self> {
+				<#ty as cornflakes::Readable>::read_from(__reader).map(Self)
+			}
+		}
+
+		impl #impl_generics cornflakes::Writable for #ident #type_generics #where_clause {
+			fn write_to(&self, __writer: &mut impl cornflakes::OrderedBufMut) -> cornflakes::WriteResult {
+				<#ty as cornflakes::Writable>::write_to(&self.0, __writer)
+			}
+		}
+	};
+	output.into()
+}
+
+/// Derives `Flag` for a fieldless enum, where the integer type of the mask is
+/// given by a `#[bitmask(Type)]` attribute and every variant has an explicit
+/// value giving its bits.
+///
+/// With `#[bitmask(Type, strict)]`, reading a `Bitmask` with bits set that
+/// don't belong to any variant is an error.
+#[proc_macro_derive(Flag, attributes(bitmask))]
+pub fn derive_flag(item: TokenStream) -> TokenStream {
+	let input = parse_macro_input!(item as DeriveInput);
+	let ident = &input.ident;
+	let attributes::Bitmask { ty, strict } = bitmask(&input.attrs);
+
+	let Data::Enum(data_enum) = &input.data else {
+		panic!("Flag can only be derived for fieldless enums");
+	};
+
+	let (variants, bits): (Vec<_>, Vec<_>) = data_enum
+		.variants
+		.iter()
+		.map(|variant| {
+			let variant_ident = &variant.ident;
+
+			assert!(
+				matches!(variant.fields, Fields::Unit),
+				"Flag can only be derived for fieldless enums"
+			);
+			let (_, bits) = variant.discriminant.as_ref().unwrap_or_else(|| {
+				panic!("`{variant_ident}` needs an explicit value, like `{variant_ident} = 0x0001`")
+			});
+
+			(variant_ident, bits)
+		})
+		.unzip();
+
+	let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+	let output = quote! {
+		impl #impl_generics cornflakes::Flag for #ident #type_generics #where_clause {
+			type Bits = #ty;
+
+			const FLAGS: &'static [Self] = &[#(Self::#variants),*];
+			const STRICT: bool = #strict;
+
+			fn bits(self) -> Self::Bits {
+				match self {
+					#(Self::#variants => #bits,)*
+				}
+			}
+		}
+
+		impl #impl_generics std::ops::BitOr for #ident #type_generics #where_clause {
+			type Output = cornflakes::Bitmask<Self>;
+
+			fn bitor(self, other: Self) -> Self::Output {
+				cornflakes::Bitmask::from(self) | other
 			}
 		}
 	};
@@ -0,0 +1,157 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use crate::attributes::sentinel;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Data, DataEnum, DeriveInput, Expr, Fields, Ident, Type};
+
+/// An enum whose variants are distinguished by sentinel values of a single
+/// value type, rather than by a discriminant.
+///
+/// Every unit variant has a `#[sentinel(value)]` attribute, and there is one
+/// variant with a single unnamed field, which is used for every other value:
+/// ```ignore
+/// enum Focus {
+///     #[sentinel(0)]
+///     None,
+///     #[sentinel(1)]
+///     PointerRoot,
+///     Window(u32),
+/// }
+/// ```
+pub struct SentinelEnum {
+	/// The variant used for values other than the sentinels.
+	value: Ident,
+	/// The type of the values.
+	ty: Type,
+	/// Each unit variant and its sentinel value.
+	sentinels: Vec<(Ident, Expr)>,
+}
+
+impl SentinelEnum {
+	/// Parses `data_enum` as a [`SentinelEnum`] if any of its variants have a
+	/// `#[sentinel(value)]` attribute.
+	pub fn parse(data_enum: &DataEnum) -> Option<Self> {
+		if !data_enum
+			.variants
+			.iter()
+			.any(|variant| sentinel(&variant.attrs).is_some())
+		{
+			return None;
+		}
+
+		let mut value = None;
+		let mut sentinels = Vec::new();
+
+		for variant in &data_enum.variants {
+			let ident = &variant.ident;
+
+			match (&variant.fields, sentinel(&variant.attrs)) {
+				(Fields::Unit, Some(expr)) => sentinels.push((ident.clone(), expr)),
+
+				(Fields::Unnamed(fields), None) if fields.unnamed.len() == 1 => {
+					assert!(
+						value.is_none(),
+						"an enum with `#[sentinel(value)]` variants can only have one variant with a field"
+					);
+
+					value = Some((ident.clone(), fields.unnamed[0].ty.clone()));
+				}
+
+				_ => panic!(
+					"`{ident}` must either be a unit variant with a `#[sentinel(value)]` attribute or have a single unnamed field"
+				),
+			}
+		}
+
+		let (value, ty) = value.unwrap_or_else(|| {
+			panic!("an enum with `#[sentinel(value)]` variants needs a variant with a single field")
+		});
+
+		Some(Self {
+			value,
+			ty,
+			sentinels,
+		})
+	}
+
+	/// Parses `input` as a [`SentinelEnum`] if it is an enum with
+	/// `#[sentinel(value)]` variants.
+	pub fn from_input(input: &DeriveInput) -> Option<Self> {
+		match &input.data {
+			Data::Enum(data_enum) => Self::parse(data_enum),
+			_ => None,
+		}
+	}
+
+	/// Generates the body of `DataSize::data_size`.
+	pub fn data_size(&self) -> TokenStream2 {
+		let Self {
+			value,
+			ty,
+			sentinels,
+		} = self;
+		let (idents, exprs): (Vec<_>, Vec<_>) = sentinels.iter().cloned().unzip();
+
+		quote! {
+			match self {
+				#(Self::#idents => <#ty as cornflakes::DataSize>::data_size(&(#exprs)),)*
+				Self::#value(__value) => <#ty as cornflakes::DataSize>::data_size(__value),
+			}
+		}
+	}
+
+	/// Generates the body of `DataSize::static_data_size_hint`.
+	pub fn static_data_size_hint(&self) -> TokenStream2 {
+		let ty = &self.ty;
+		quote!(<#ty as cornflakes::DataSize>::static_data_size_hint())
+	}
+
+	/// Generates the value of `StaticDataSize::SIZE`.
+	pub fn static_data_size(&self) -> TokenStream2 {
+		let ty = &self.ty;
+		quote!(<#ty as cornflakes::StaticDataSize>::SIZE)
+	}
+
+	/// Generates the body of `Readable::read_from`, which reads a value and
+	/// returns the variant of the sentinel it is equal to, if any.
+	pub fn readable(&self) -> TokenStream2 {
+		let Self {
+			value,
+			ty,
+			sentinels,
+		} = self;
+		let (idents, exprs): (Vec<_>, Vec<_>) = sentinels.iter().cloned().unzip();
+
+		quote! {
+			let __value = <#ty as cornflakes::Readable>::read_from(__reader)?;
+
+			#(
+				if __value == (#exprs) {
+					return Ok(Self::#idents);
+				}
+			)*
+
+			Ok(Self::#value(__value))
+		}
+	}
+
+	/// Generates the body of `Writable::write_to`.
+	pub fn writable(&self) -> TokenStream2 {
+		let Self {
+			value,
+			ty,
+			sentinels,
+		} = self;
+		let (idents, exprs): (Vec<_>, Vec<_>) = sentinels.iter().cloned().unzip();
+
+		quote! {
+			match self {
+				#(Self::#idents => <#ty as cornflakes::Writable>::write_to(&(#exprs), __writer),)*
+				Self::#value(__value) => <#ty as cornflakes::Writable>::write_to(__value, __writer),
+			}
+		}
+	}
+}
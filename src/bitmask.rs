@@ -0,0 +1,265 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use crate::{
+	DataSize, OrderedBuf, OrderedBufMut, ReadError, ReadResult, Readable, StaticDataSize, Writable,
+	WriteResult,
+};
+use num::{PrimInt, ToPrimitive, Zero};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::ops::{BitOr, BitOrAssign};
+
+/// A single flag of a [`Bitmask`], usually a fieldless `enum`.
+///
+/// This can be derived with a `#[bitmask(Type)]` attribute giving the integer
+/// type of the mask, and explicit values for every variant:
+/// ```
+/// use cornflakes::derive::Flag;
+///
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Flag)]
+/// #[bitmask(u16, strict)]
+/// enum KeyButMask {
+///     Shift = 0x0001,
+///     Lock = 0x0002,
+///     Control = 0x0004,
+/// }
+/// ```
+pub trait Flag: Copy + 'static {
+	/// The integer type with which a [`Bitmask`] of these flags is read and
+	/// written.
+	type Bits: PrimInt + Hash + Debug + Readable + Writable + StaticDataSize;
+
+	/// Every flag.
+	const FLAGS: &'static [Self];
+
+	/// Whether reading a [`Bitmask`] with bits set that don't belong to any
+	/// flag returns [`ReadError::ReservedBitsSet`]. Otherwise, those bits are
+	/// kept.
+	const STRICT: bool;
+
+	/// Returns the bits of this flag.
+	fn bits(self) -> Self::Bits;
+}
+
+/// A set of [flags](Flag), written as the bits of those flags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bitmask<F: Flag> {
+	bits: F::Bits,
+}
+
+impl<F: Flag> Bitmask<F> {
+	/// Returns a [`Bitmask`] with no flags set.
+	#[must_use]
+	pub fn empty() -> Self {
+		Self {
+			bits: F::Bits::zero(),
+		}
+	}
+
+	/// Returns a [`Bitmask`] with every flag set.
+	#[must_use]
+	pub fn all() -> Self {
+		F::FLAGS.iter().copied().collect()
+	}
+
+	/// Returns a [`Bitmask`] with the given `bits`, or `None` if any of the
+	/// bits don't belong to a flag.
+	#[must_use]
+	pub fn from_bits(bits: F::Bits) -> Option<Self> {
+		let mask = Self::from_bits_retain(bits);
+		(mask.reserved_bits() == F::Bits::zero()).then_some(mask)
+	}
+
+	/// Returns a [`Bitmask`] with the given `bits`, keeping any bits which
+	/// don't belong to a flag.
+	#[must_use]
+	pub const fn from_bits_retain(bits: F::Bits) -> Self {
+		Self { bits }
+	}
+
+	/// Returns the bits of the mask.
+	#[must_use]
+	pub const fn bits(&self) -> F::Bits {
+		self.bits
+	}
+
+	/// Returns the bits of the mask which don't belong to any flag.
+	#[must_use]
+	pub fn reserved_bits(&self) -> F::Bits {
+		self.bits & !Self::all().bits
+	}
+
+	/// Returns whether no bits are set.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.bits == F::Bits::zero()
+	}
+
+	/// Returns whether `flag` is set.
+	#[must_use]
+	pub fn contains(&self, flag: F) -> bool {
+		self.bits & flag.bits() == flag.bits()
+	}
+
+	/// Sets `flag`.
+	pub fn insert(&mut self, flag: F) {
+		self.bits = self.bits | flag.bits();
+	}
+
+	/// Unsets `flag`.
+	pub fn remove(&mut self, flag: F) {
+		self.bits = self.bits & !flag.bits();
+	}
+
+	/// Returns an iterator over the flags which are set.
+	pub fn flags(&self) -> impl Iterator<Item = F> + '_ {
+		F::FLAGS.iter().copied().filter(|flag| self.contains(*flag))
+	}
+}
+
+impl<F: Flag> Default for Bitmask<F> {
+	fn default() -> Self {
+		Self::empty()
+	}
+}
+
+impl<F: Flag> From<F> for Bitmask<F> {
+	fn from(flag: F) -> Self {
+		Self::from_bits_retain(flag.bits())
+	}
+}
+
+impl<F: Flag> FromIterator<F> for Bitmask<F> {
+	fn from_iter<I: IntoIterator<Item = F>>(iter: I) -> Self {
+		let mut mask = Self::empty();
+
+		for flag in iter {
+			mask.insert(flag);
+		}
+
+		mask
+	}
+}
+
+impl<F: Flag> BitOr<F> for Bitmask<F> {
+	type Output = Self;
+
+	fn bitor(mut self, flag: F) -> Self::Output {
+		self.insert(flag);
+		self
+	}
+}
+
+impl<F: Flag> BitOr for Bitmask<F> {
+	type Output = Self;
+
+	fn bitor(self, other: Self) -> Self::Output {
+		Self::from_bits_retain(self.bits | other.bits)
+	}
+}
+
+impl<F: Flag> BitOrAssign<F> for Bitmask<F> {
+	fn bitor_assign(&mut self, flag: F) {
+		self.insert(flag);
+	}
+}
+
+impl<F: Flag> DataSize for Bitmask<F> {
+	fn data_size(&self) -> usize {
+		F::Bits::SIZE
+	}
+
+	fn static_data_size_hint() -> Option<usize> {
+		Some(F::Bits::SIZE)
+	}
+}
+
+impl<F: Flag> StaticDataSize for Bitmask<F> {
+	const SIZE: usize = F::Bits::SIZE;
+}
+
+impl<F: Flag> Readable for Bitmask<F> {
+	fn read_from(reader: &mut impl OrderedBuf) -> ReadResult<Self> {
+		let mask = Self::from_bits_retain(F::Bits::read_from(reader)?);
+		let reserved = mask.reserved_bits();
+
+		if F::STRICT && reserved != F::Bits::zero() {
+			return Err(ReadError::ReservedBitsSet {
+				bits: reserved.to_u64().unwrap_or(u64::MAX),
+			});
+		}
+
+		Ok(mask)
+	}
+}
+
+impl<F: Flag> Writable for Bitmask<F> {
+	fn write_to(&self, writer: &mut impl OrderedBufMut) -> WriteResult {
+		self.bits.write_to(writer)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::{Bitmask, Flag};
+	use crate::{ReadError, Readable, Writable};
+
+	#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+	enum TestFlag {
+		A,
+		B,
+	}
+
+	impl Flag for TestFlag {
+		type Bits = u8;
+
+		const FLAGS: &'static [Self] = &[Self::A, Self::B];
+		const STRICT: bool = true;
+
+		fn bits(self) -> u8 {
+			match self {
+				Self::A => 0x01,
+				Self::B => 0x04,
+			}
+		}
+	}
+
+	#[test]
+	fn test_bitmask() {
+		let mut mask = Bitmask::from(TestFlag::A);
+		assert!(mask.contains(TestFlag::A));
+		assert!(!mask.contains(TestFlag::B));
+
+		mask |= TestFlag::B;
+		assert_eq!(mask.bits(), 0x05);
+		assert_eq!(mask, Bitmask::all());
+		assert_eq!(mask.flags().collect::<Vec<_>>(), [TestFlag::A, TestFlag::B]);
+
+		mask.remove(TestFlag::A);
+		assert_eq!(mask.bits(), 0x04);
+
+		assert_eq!(Bitmask::<TestFlag>::from_bits(0x02), None);
+		assert_eq!(
+			Bitmask::<TestFlag>::from_bits_retain(0x02).reserved_bits(),
+			0x02
+		);
+	}
+
+	#[test]
+	fn test_bitmask_round_trip() {
+		let mask = Bitmask::from(TestFlag::B);
+
+		let mut bytes = Vec::new();
+		mask.write_to(&mut bytes).unwrap();
+		assert_eq!(bytes, [0x04]);
+		assert_eq!(Bitmask::read_from(&mut &bytes[..]).unwrap(), mask);
+	}
+
+	#[test]
+	fn test_bitmask_strict() {
+		let error = Bitmask::<TestFlag>::read_from(&mut &[0x83][..]).unwrap_err();
+		assert!(matches!(error, ReadError::ReservedBitsSet { bits: 0x82 }));
+	}
+}
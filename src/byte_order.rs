@@ -0,0 +1,299 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use bytes::buf::{Chain, Limit, Take, UninitSlice};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use std::collections::VecDeque;
+use std::io::Cursor;
+use std::mem::MaybeUninit;
+
+/// The order in which the bytes of multi-byte numbers are read and written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ByteOrder {
+	/// The most significant byte comes first.
+	///
+	/// This is the byte order used by [`Buf`] and [`BufMut`] by default.
+	#[default]
+	BigEndian,
+	/// The least significant byte comes first.
+	LittleEndian,
+	/// The byte order of the target platform.
+	Native,
+}
+
+impl ByteOrder {
+	/// Returns whether this byte order is big endian on the target platform.
+	#[must_use]
+	pub const fn is_big_endian(self) -> bool {
+		match self {
+			Self::BigEndian => true,
+			Self::LittleEndian => false,
+			Self::Native => cfg!(target_endian = "big"),
+		}
+	}
+}
+
+/// A [`Buf`] from which numbers are read in a particular [`ByteOrder`].
+///
+/// Every [`Readable`](crate::Readable) type is read from an `OrderedBuf`, and
+/// reads numbers in its byte order. Buffers are big endian unless they are
+/// wrapped in [`ByteOrdered`], and adapters such as [`Take`] read in the byte
+/// order of the buffer they wrap.
+///
+/// [`Buf`]'s own methods, such as [`Buf::get_u16`], always read big endian
+/// numbers, so numbers should be read with their
+/// [`Readable`](crate::Readable) implementations instead.
+pub trait OrderedBuf: Buf {
+	/// Returns the [`ByteOrder`] in which numbers are read from this buffer.
+	fn byte_order(&self) -> ByteOrder {
+		ByteOrder::BigEndian
+	}
+}
+
+/// A [`BufMut`] to which numbers are written in a particular [`ByteOrder`].
+///
+/// Every [`Writable`](crate::Writable) type is written to an `OrderedBufMut`,
+/// and writes numbers in its byte order. Buffers are big endian unless they
+/// are wrapped in [`ByteOrdered`], and adapters such as [`Limit`] write in the
+/// byte order of the buffer they wrap.
+///
+/// [`BufMut`]'s own methods, such as [`BufMut::put_u16`], always write big
+/// endian numbers, so numbers should be written with their
+/// [`Writable`](crate::Writable) implementations instead.
+pub trait OrderedBufMut: BufMut {
+	/// Returns the [`ByteOrder`] in which numbers are written to this buffer.
+	fn byte_order(&self) -> ByteOrder {
+		ByteOrder::BigEndian
+	}
+}
+
+impl OrderedBuf for &[u8] {}
+impl OrderedBuf for Bytes {}
+impl OrderedBuf for BytesMut {}
+impl OrderedBuf for VecDeque<u8> {}
+impl<T: AsRef<[u8]>> OrderedBuf for Cursor<T> {}
+
+impl OrderedBufMut for Vec<u8> {}
+impl OrderedBufMut for BytesMut {}
+impl OrderedBufMut for &mut [u8] {}
+impl OrderedBufMut for &mut [MaybeUninit<u8>] {}
+
+impl<B: OrderedBuf + ?Sized> OrderedBuf for &mut B {
+	fn byte_order(&self) -> ByteOrder {
+		(**self).byte_order()
+	}
+}
+
+impl<B: OrderedBuf + ?Sized> OrderedBuf for Box<B> {
+	fn byte_order(&self) -> ByteOrder {
+		(**self).byte_order()
+	}
+}
+
+impl<B: OrderedBuf> OrderedBuf for Take<B> {
+	fn byte_order(&self) -> ByteOrder {
+		self.get_ref().byte_order()
+	}
+}
+
+/// Reads in the byte order of the first buffer.
+impl<A: OrderedBuf, B: OrderedBuf> OrderedBuf for Chain<A, B> {
+	fn byte_order(&self) -> ByteOrder {
+		self.first_ref().byte_order()
+	}
+}
+
+impl<B: OrderedBufMut + ?Sized> OrderedBufMut for &mut B {
+	fn byte_order(&self) -> ByteOrder {
+		(**self).byte_order()
+	}
+}
+
+impl<B: OrderedBufMut + ?Sized> OrderedBufMut for Box<B> {
+	fn byte_order(&self) -> ByteOrder {
+		(**self).byte_order()
+	}
+}
+
+impl<B: OrderedBufMut> OrderedBufMut for Limit<B> {
+	fn byte_order(&self) -> ByteOrder {
+		self.get_ref().byte_order()
+	}
+}
+
+/// Writes in the byte order of the first buffer.
+impl<A: OrderedBufMut, B: OrderedBufMut> OrderedBufMut for Chain<A, B> {
+	fn byte_order(&self) -> ByteOrder {
+		self.first_ref().byte_order()
+	}
+}
+
+/// Wraps a [`Buf`] or [`BufMut`] so that numbers are read or written in the
+/// given [`ByteOrder`].
+///
+/// The wrapped buffer is an [`OrderedBuf`] or [`OrderedBufMut`] in that byte
+/// order, so reading from or writing to a `ByteOrdered` buffer applies its
+/// byte order to everything read or written, including derived types.
+///
+/// # Examples
+/// ```
+/// use cornflakes::{ByteOrder, ByteOrdered, Readable};
+///
+/// let mut reader = ByteOrdered::new(&[0x01, 0x02][..], ByteOrder::LittleEndian);
+/// assert_eq!(u16::read_from(&mut reader).unwrap(), 0x0201);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ByteOrdered<B> {
+	inner: B,
+	byte_order: ByteOrder,
+}
+
+impl<B> ByteOrdered<B> {
+	/// Wraps `inner` so that numbers are read or written in `byte_order`.
+	pub const fn new(inner: B, byte_order: ByteOrder) -> Self {
+		Self { inner, byte_order }
+	}
+
+	/// Returns the [`ByteOrder`] with which numbers are read or written.
+	pub const fn byte_order(&self) -> ByteOrder {
+		self.byte_order
+	}
+
+	/// Changes the [`ByteOrder`] with which numbers are read or written.
+	pub const fn set_byte_order(&mut self, byte_order: ByteOrder) {
+		self.byte_order = byte_order;
+	}
+
+	/// Returns a reference to the wrapped buffer.
+	pub const fn get_ref(&self) -> &B {
+		&self.inner
+	}
+
+	/// Returns a mutable reference to the wrapped buffer.
+	pub const fn get_mut(&mut self) -> &mut B {
+		&mut self.inner
+	}
+
+	/// Unwraps the wrapped buffer.
+	pub fn into_inner(self) -> B {
+		self.inner
+	}
+}
+
+impl<B: Buf> Buf for ByteOrdered<B> {
+	fn remaining(&self) -> usize {
+		self.inner.remaining()
+	}
+
+	fn chunk(&self) -> &[u8] {
+		self.inner.chunk()
+	}
+
+	fn advance(&mut self, cnt: usize) {
+		self.inner.advance(cnt);
+	}
+
+	fn copy_to_bytes(&mut self, len: usize) -> Bytes {
+		self.inner.copy_to_bytes(len)
+	}
+}
+
+impl<B: Buf> OrderedBuf for ByteOrdered<B> {
+	fn byte_order(&self) -> ByteOrder {
+		self.byte_order
+	}
+}
+
+// SAFETY: every method is forwarded to `inner`, which upholds `BufMut`'s
+// invariants.
+unsafe impl<B: BufMut> BufMut for ByteOrdered<B> {
+	fn remaining_mut(&self) -> usize {
+		self.inner.remaining_mut()
+	}
+
+	unsafe fn advance_mut(&mut self, cnt: usize) {
+		self.inner.advance_mut(cnt);
+	}
+
+	fn chunk_mut(&mut self) -> &mut UninitSlice {
+		self.inner.chunk_mut()
+	}
+
+	fn put_slice(&mut self, src: &[u8]) {
+		self.inner.put_slice(src);
+	}
+}
+
+impl<B: BufMut> OrderedBufMut for ByteOrdered<B> {
+	fn byte_order(&self) -> ByteOrder {
+		self.byte_order
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::{ByteOrder, ByteOrdered};
+	use crate::{ContextualReadable, Readable, Writable};
+	use bytes::{Buf, BufMut};
+
+	#[test]
+	fn test_read_big_endian() {
+		let mut reader = ByteOrdered::new(&[0x01, 0x02, 0x03, 0x04][..], ByteOrder::BigEndian);
+		assert_eq!(u32::read_from(&mut reader).unwrap(), 0x0102_0304);
+	}
+
+	#[test]
+	fn test_read_little_endian() {
+		let mut reader = ByteOrdered::new(&[0x01, 0x02, 0x03, 0x04][..], ByteOrder::LittleEndian);
+		assert_eq!(
+			<[u16; 2]>::read_from(&mut reader).unwrap(),
+			[0x0201, 0x0403]
+		);
+	}
+
+	#[test]
+	fn test_read_native() {
+		let bytes = 0x0102_0304_u32.to_ne_bytes();
+		let mut reader = ByteOrdered::new(&bytes[..], ByteOrder::Native);
+		assert_eq!(u32::read_from(&mut reader).unwrap(), 0x0102_0304);
+	}
+
+	#[test]
+	fn test_write_little_endian() {
+		let mut writer = ByteOrdered::new(Vec::new(), ByteOrder::LittleEndian);
+		vec![0x0102_u16, 0x0304].write_to(&mut writer).unwrap();
+		(-2_i32).write_to(&mut writer).unwrap();
+
+		assert_eq!(
+			writer.into_inner(),
+			[0x02, 0x01, 0x04, 0x03, 0xfe, 0xff, 0xff, 0xff]
+		);
+	}
+
+	#[test]
+	fn test_adapters_keep_byte_order() {
+		let reader = ByteOrdered::new(&[0x01, 0x00, 0x02, 0x00][..], ByteOrder::LittleEndian);
+		let mut take = reader.take(2);
+		assert_eq!(u16::read_from(&mut take).unwrap(), 0x0001);
+
+		let mut chain = take.into_inner().chain(&[0x03, 0x00][..]);
+		assert_eq!(
+			Vec::<u16>::read_with(&mut chain, &2).unwrap(),
+			[0x0002, 0x0003]
+		);
+
+		let mut limit = ByteOrdered::new(Vec::new(), ByteOrder::LittleEndian).limit(4);
+		[0x0102_u16, 0x0304].write_to(&mut limit).unwrap();
+		assert_eq!(limit.into_inner().into_inner(), [0x02, 0x01, 0x04, 0x03]);
+	}
+
+	#[test]
+	fn test_round_trip_float() {
+		let mut writer = ByteOrdered::new(Vec::new(), ByteOrder::LittleEndian);
+		1.5_f64.write_to(&mut writer).unwrap();
+
+		let mut reader = ByteOrdered::new(&writer.get_ref()[..], ByteOrder::LittleEndian);
+		assert!((f64::read_from(&mut reader).unwrap() - 1.5).abs() < f64::EPSILON);
+	}
+}
@@ -3,6 +3,8 @@
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
 use crate::{DataSize, StaticDataSize};
+use bytes::Bytes;
+use std::marker::PhantomData;
 
 // Implementations for primitive types used in xrb
 
@@ -11,13 +13,15 @@ macro_rules! static_data_size {
 	($($type:ty),+$(,)?) => {
 		$(
 			impl StaticDataSize for $type {
-				fn static_data_size() -> usize {
-					std::mem::size_of::<$type>()
-				}
+				const SIZE: usize = std::mem::size_of::<$type>();
 			}
 			impl DataSize for $type {
 				fn data_size(&self) -> usize {
-					Self::static_data_size()
+					Self::SIZE
+				}
+
+				fn static_data_size_hint() -> Option<usize> {
+					Some(Self::SIZE)
 				}
 			}
 		)+
@@ -43,41 +47,44 @@ static_data_size! {
 	bool,
 }
 
+/// Returns the size of `list`, multiplying its length by the size of `T` if
+/// `T` has a static size.
+fn list_data_size<T: DataSize>(list: &[T]) -> usize {
+	T::static_data_size_hint().map_or_else(
+		|| list.iter().map(DataSize::data_size).sum(),
+		|size| size * list.len(),
+	)
+}
+
 impl<T: DataSize> DataSize for Vec<T> {
 	fn data_size(&self) -> usize {
-		self.iter().map(|v| v.data_size()).sum()
+		list_data_size(self)
 	}
 }
 
 impl<T: DataSize, const N: usize> DataSize for [T; N] {
 	fn data_size(&self) -> usize {
-		let mut data_size = 0;
-
-		for element in self {
-			data_size += element.data_size();
-		}
+		list_data_size(self)
+	}
 
-		data_size
+	fn static_data_size_hint() -> Option<usize> {
+		T::static_data_size_hint().map(|size| size * N)
 	}
 }
 
+impl<T: StaticDataSize, const N: usize> StaticDataSize for [T; N] {
+	const SIZE: usize = T::SIZE * N;
+}
+
 impl<T: DataSize> DataSize for &[T] {
 	fn data_size(&self) -> usize {
-		let size: &mut usize = &mut 0;
-		for e in *self {
-			*size += e.data_size();
-		}
-		*size
+		list_data_size(self)
 	}
 }
 
 impl<T: DataSize> DataSize for [T] {
 	fn data_size(&self) -> usize {
-		let size: &mut usize = &mut 0;
-		for e in self {
-			*size += e.data_size();
-		}
-		*size
+		list_data_size(self)
 	}
 }
 
@@ -87,62 +94,203 @@ impl DataSize for &str {
 	}
 }
 
+impl DataSize for str {
+	fn data_size(&self) -> usize {
+		self.len()
+	}
+}
+
+impl DataSize for String {
+	fn data_size(&self) -> usize {
+		self.len()
+	}
+}
+
+impl DataSize for Bytes {
+	fn data_size(&self) -> usize {
+		self.len()
+	}
+}
+
+/// An [`Option`] is encoded with a [`Sentinel`](crate::Sentinel) value when
+/// `T` has a static size, so it is always the size of `T`. This relies on
+/// `T`'s [`static_data_size_hint`](DataSize::static_data_size_hint), which
+/// [`StaticDataSize`] types must return.
+///
+/// Otherwise, there is no sentinel of the right size, so `None` is omitted and
+/// has a size of zero.
 impl<T: DataSize> DataSize for Option<T> {
-	default fn data_size(&self) -> usize {
-		match &self {
-			None => 1,
-			Some(v) => v.data_size(),
-		}
+	fn data_size(&self) -> usize {
+		T::static_data_size_hint().unwrap_or_else(|| self.as_ref().map_or(0, DataSize::data_size))
+	}
+
+	fn static_data_size_hint() -> Option<usize> {
+		T::static_data_size_hint()
 	}
 }
 
 impl<T: StaticDataSize> StaticDataSize for Option<T> {
-	fn static_data_size() -> usize {
-		T::static_data_size()
-	}
+	const SIZE: usize = T::SIZE;
 }
 
 // Size for references will be the same as the owned type.
 
 impl<T: DataSize> DataSize for &T {
-	default fn data_size(&self) -> usize {
-		T::data_size(&self)
+	fn data_size(&self) -> usize {
+		T::data_size(self)
+	}
+
+	fn static_data_size_hint() -> Option<usize> {
+		T::static_data_size_hint()
 	}
 }
 
 impl<T: DataSize + StaticDataSize> StaticDataSize for &T {
-	fn static_data_size() -> usize {
-		T::static_data_size()
-	}
+	const SIZE: usize = T::SIZE;
 }
 
 impl<T: DataSize> DataSize for &mut T {
-	default fn data_size(&self) -> usize {
-		T::data_size(&self)
+	fn data_size(&self) -> usize {
+		T::data_size(self)
+	}
+
+	fn static_data_size_hint() -> Option<usize> {
+		T::static_data_size_hint()
 	}
 }
 
 impl<T: StaticDataSize> StaticDataSize for &mut T {
-	fn static_data_size() -> usize {
-		T::static_data_size()
-	}
+	const SIZE: usize = T::SIZE;
 }
 
 impl<T: DataSize> DataSize for Box<T> {
-	default fn data_size(&self) -> usize {
-		T::data_size(&self)
+	fn data_size(&self) -> usize {
+		T::data_size(self)
+	}
+
+	fn static_data_size_hint() -> Option<usize> {
+		T::static_data_size_hint()
 	}
 }
 
 impl<T: StaticDataSize> StaticDataSize for Box<T> {
-	fn static_data_size() -> usize {
-		T::static_data_size()
+	const SIZE: usize = T::SIZE;
+}
+
+impl DataSize for () {
+	fn data_size(&self) -> usize {
+		0
+	}
+
+	fn static_data_size_hint() -> Option<usize> {
+		Some(0)
+	}
+}
+
+impl StaticDataSize for () {
+	const SIZE: usize = 0;
+}
+
+// The size of a tuple is the sum of the sizes of its elements.
+macro_rules! tuples {
+	($(($($ty:ident $index:tt),+)),+$(,)?) => {
+		$(
+			impl<$($ty: DataSize),+> DataSize for ($($ty,)+) {
+				fn data_size(&self) -> usize {
+					0 $(+ self.$index.data_size())+
+				}
+
+				fn static_data_size_hint() -> Option<usize> {
+					Some(0 $(+ $ty::static_data_size_hint()?)+)
+				}
+			}
+
+			impl<$($ty: StaticDataSize),+> StaticDataSize for ($($ty,)+) {
+				const SIZE: usize = 0 $(+ $ty::SIZE)+;
+			}
+		)+
+	};
+}
+
+tuples! {
+	(A 0),
+	(A 0, B 1),
+	(A 0, B 1, C 2),
+	(A 0, B 1, C 2, D 3),
+	(A 0, B 1, C 2, D 3, E 4),
+	(A 0, B 1, C 2, D 3, E 4, F 5),
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6),
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7),
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8),
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9),
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10),
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11),
+}
+
+impl<T: ?Sized> DataSize for PhantomData<T> {
+	fn data_size(&self) -> usize {
+		0
+	}
+
+	fn static_data_size_hint() -> Option<usize> {
+		Some(0)
 	}
 }
 
+impl<T: ?Sized> StaticDataSize for PhantomData<T> {
+	const SIZE: usize = 0;
+}
+
 #[cfg(test)]
 mod test {
-	use super::DataSize;
+	use super::{DataSize, StaticDataSize};
+	use crate::{OrderedBuf, OrderedBufMut, ReadResult, Readable, Sentinel, Writable, WriteResult};
+	use std::marker::PhantomData;
+
+	// Hand-written `Sentinel` keys, with and without their size hint.
+	macro_rules! keys {
+		($($key:ident => $hint:expr),+$(,)?) => {
+			$(
+				#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+				struct $key(u8);
+
+				impl DataSize for $key {
+					fn data_size(&self) -> usize {
+						Self::SIZE
+					}
+
+					fn static_data_size_hint() -> Option<usize> {
+						$hint
+					}
+				}
+
+				impl StaticDataSize for $key {
+					const SIZE: usize = 1;
+				}
+
+				impl Readable for $key {
+					fn read_from(reader: &mut impl OrderedBuf) -> ReadResult<Self> {
+						u8::read_from(reader).map(Self)
+					}
+				}
+
+				impl Writable for $key {
+					fn write_to(&self, writer: &mut impl OrderedBufMut) -> WriteResult {
+						self.0.write_to(writer)
+					}
+				}
+
+				impl Sentinel for $key {
+					const SENTINEL: Self = Self(0);
+				}
+			)+
+		};
+	}
+
+	keys! {
+		Key => Some(Self::SIZE),
+		ForgetfulKey => None,
+	}
 
 	#[test]
 	fn test_datasize_vec() {
@@ -160,6 +308,41 @@ mod test {
 	fn test_datasize_option_dynamic() {
 		let data: Option<Vec<i64>> = Some(vec![i64::default(); 10]);
 		assert_eq!(data.data_size(), 80);
+
+		let data: Option<Vec<i64>> = None;
+		assert_eq!(data.data_size(), 0);
+	}
+
+	#[test]
+	fn test_datasize_option_sentinel() {
+		for key in [None, Some(Key(5))] {
+			let mut bytes = Vec::new();
+			key.write_to(&mut bytes).unwrap();
+			assert_eq!(key.data_size(), bytes.len());
+		}
+
+		assert_eq!(<Option<Key> as StaticDataSize>::SIZE, 1);
+		assert_eq!((None::<Key>, 0u32).data_size(), 5);
+	}
+
+	#[test]
+	#[cfg(debug_assertions)]
+	#[should_panic(expected = "`static_data_size_hint` must return `Some(SIZE)`")]
+	fn test_datasize_option_sentinel_without_hint() {
+		let _ = None::<ForgetfulKey>.write_to(&mut Vec::new());
+	}
+
+	#[test]
+	fn test_static_size_array() {
+		assert_eq!(<[u32; 4] as StaticDataSize>::SIZE, 16);
+		assert_eq!(<[u32; 4] as DataSize>::static_data_size_hint(), Some(16));
+	}
+
+	#[test]
+	fn test_datasize_tuple() {
+		assert_eq!((1u8, vec![0u16; 3]).data_size(), 7);
+		assert_eq!(<(u8, Vec<u16>)>::static_data_size_hint(), None);
+		assert_eq!(<(u8, (), PhantomData<u64>, u32) as StaticDataSize>::SIZE, 5);
 	}
 
 	// TODO: More tests ?
@@ -0,0 +1,82 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::collections::VecDeque;
+use std::fmt::{self, Display, Formatter};
+
+/// A single step in a [`FieldPath`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+	/// A named or tuple field, like `.value` or `.0`.
+	Field(&'static str),
+	/// An element of a list or array, like `[3]`.
+	Index(usize),
+}
+
+impl Display for PathSegment {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Field(name) => write!(f, ".{name}"),
+			Self::Index(index) => write!(f, "[{index}]"),
+		}
+	}
+}
+
+/// The path to the value that was being read when a
+/// [`ReadError`](crate::ReadError) occurred, such as
+/// `GetPropertyReply.value[3]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct FieldPath {
+	root: Option<&'static str>,
+	segments: VecDeque<PathSegment>,
+}
+
+impl FieldPath {
+	/// Returns the name of the outermost type in the path, if it is known.
+	#[must_use]
+	pub const fn root(&self) -> Option<&'static str> {
+		self.root
+	}
+
+	/// Returns the [segments](PathSegment) of the path, outermost first.
+	pub fn segments(&self) -> impl Iterator<Item = &PathSegment> {
+		self.segments.iter()
+	}
+
+	/// Adds a field of the type `root` to the start of the path.
+	pub(crate) fn push_field(&mut self, root: &'static str, name: &'static str) {
+		self.root = Some(root);
+		self.segments.push_front(PathSegment::Field(name));
+	}
+
+	/// Adds a field of an unnamed type, like a tuple, to the start of the path.
+	///
+	/// The root type is cleared, as the path no longer starts at it.
+	pub(crate) fn push_tuple_field(&mut self, name: &'static str) {
+		self.root = None;
+		self.segments.push_front(PathSegment::Field(name));
+	}
+
+	/// Adds an index to the start of the path.
+	///
+	/// The root type is cleared, as the path no longer starts at it.
+	pub(crate) fn push_index(&mut self, index: usize) {
+		self.root = None;
+		self.segments.push_front(PathSegment::Index(index));
+	}
+}
+
+impl Display for FieldPath {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		if let Some(root) = self.root {
+			write!(f, "{root}")?;
+		}
+
+		for segment in &self.segments {
+			write!(f, "{segment}")?;
+		}
+
+		Ok(())
+	}
+}
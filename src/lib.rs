@@ -2,9 +2,6 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
-// We need specialization to implement DataSize for Wrapper types like Option<T>
-#![allow(incomplete_features)]
-#![feature(specialization)]
 // Deny the following clippy lints to enforce them:
 #![deny(clippy::complexity)]
 #![deny(clippy::correctness)]
@@ -21,62 +18,326 @@
 #![allow(clippy::doc_markdown)]
 #![allow(clippy::wildcard_imports)]
 #![allow(clippy::module_name_repetitions)]
+// `readme = false` is intentional.
+#![allow(clippy::cargo_common_metadata)]
 
-use num::Zero;
 use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::mem::MaybeUninit;
+use std::str::Utf8Error;
 use thiserror::Error;
 
 pub type ReadResult<T> = Result<T, ReadError>;
 pub type WriteResult = Result<(), WriteError>;
 
-pub use bytes::{Buf, BufMut};
+pub use bitmask::{Bitmask, Flag};
+pub use byte_order::{ByteOrder, ByteOrdered, OrderedBuf, OrderedBufMut};
+pub use bytes::{Buf, BufMut, Bytes};
+pub use field_path::{FieldPath, PathSegment};
+pub use limits::Limits;
+pub use option::{Flagged, Omittable};
+pub use padding::{pad, Pad};
+pub use prefixed::{Length, Prefixed};
+pub use readable::ensure_remaining;
+pub use string::{Latin1, NulTerminated};
+pub use writable::ensure_capacity;
+
+/// The discriminant of an enum variant, at the width with which it is read
+/// and written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Discriminant {
+	U8(u8),
+	U16(u16),
+	U32(u32),
+	U64(u64),
+}
+
+impl Display for Discriminant {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::U8(discriminant) => write!(f, "{discriminant}"),
+			Self::U16(discriminant) => write!(f, "{discriminant}"),
+			Self::U32(discriminant) => write!(f, "{discriminant}"),
+			Self::U64(discriminant) => write!(f, "{discriminant}"),
+		}
+	}
+}
+
+macro_rules! discriminant_from {
+	($($ty:ty => $variant:ident),*$(,)?) => {
+		$(
+			impl From<$ty> for Discriminant {
+				fn from(discriminant: $ty) -> Self {
+					Self::$variant(discriminant)
+				}
+			}
+		)*
+	};
+}
+
+discriminant_from! {
+	u8 => U8,
+	u16 => U16,
+	u32 => U32,
+	u64 => U64,
+}
 
 #[non_exhaustive]
 #[derive(Error, Debug)]
 pub enum ReadError {
 	#[error("unrecognized variant discriminant: {0}")]
-	UnrecognizedDiscriminant(u8),
+	UnrecognizedDiscriminant(Discriminant),
+
+	#[error("unexpected end of input: needed {needed} bytes, but only {available} remain")]
+	UnexpectedEof { needed: usize, available: usize },
+
+	/// An error that occurred while reading the value at `path`, which began
+	/// `offset` bytes into the outermost value being read.
+	#[error("failed to read `{path}` at byte {offset}: {source}")]
+	InField {
+		path: FieldPath,
+		offset: usize,
+		source: Box<Self>,
+	},
+
+	#[error("invalid UTF-8: {0}")]
+	InvalidUtf8(Utf8Error),
+
+	/// Bits were set in a strict [`Bitmask`] which don't belong to any of its
+	/// [flags](Flag).
+	#[error("reserved bits are set: {bits:#x}")]
+	ReservedBitsSet { bits: u64 },
+
+	/// A list was longer than the [`Limits`] with which it was read allow.
+	#[error("list length {length} exceeds the maximum length of {max}")]
+	LengthLimitExceeded { length: usize, max: usize },
+
+	/// A list was larger than the [`Limits`] with which it was read allow.
+	#[error("list is larger than the maximum size of {max} bytes")]
+	SizeLimitExceeded { max: usize },
 
 	#[error("{0}")]
 	Other(Box<dyn Error>),
 }
 
+impl ReadError {
+	/// Records that this error occurred while reading the field `name` of the
+	/// type `root`, where that field began `offset` bytes into `root`.
+	///
+	/// This is called by derived [`Readable`] implementations so that the
+	/// [`FieldPath`] and offset are built up as the error is returned.
+	#[must_use]
+	pub fn in_field(self, root: &'static str, name: &'static str, offset: usize) -> Self {
+		self.within(offset, |path| path.push_field(root, name))
+	}
+
+	/// Records that this error occurred while reading the field `name` of a
+	/// tuple, like `0`, where that field began `offset` bytes into the tuple.
+	#[must_use]
+	pub fn in_tuple_field(self, name: &'static str, offset: usize) -> Self {
+		self.within(offset, |path| path.push_tuple_field(name))
+	}
+
+	/// Records that this error occurred while reading the element at `index`
+	/// of a list, where that element began `offset` bytes into the list.
+	#[must_use]
+	pub fn at_index(self, index: usize, offset: usize) -> Self {
+		self.within(offset, |path| path.push_index(index))
+	}
+
+	fn within(self, offset: usize, push: impl FnOnce(&mut FieldPath)) -> Self {
+		match self {
+			Self::InField {
+				mut path,
+				offset: inner_offset,
+				source,
+			} => {
+				push(&mut path);
+
+				Self::InField {
+					path,
+					offset: offset + inner_offset,
+					source,
+				}
+			}
+
+			error => {
+				let mut path = FieldPath::default();
+				push(&mut path);
+
+				Self::InField {
+					path,
+					offset,
+					source: Box::new(error),
+				}
+			}
+		}
+	}
+
+	/// Returns the underlying error, without any [`FieldPath`] information.
+	#[must_use]
+	pub fn root_cause(&self) -> &Self {
+		match self {
+			Self::InField { source, .. } => source.root_cause(),
+			error => error,
+		}
+	}
+}
+
 #[non_exhaustive]
 #[derive(Error, Debug)]
 pub enum WriteError {
+	#[error("insufficient capacity: {required} bytes required, but only {remaining} remain")]
+	InsufficientCapacity { required: usize, remaining: usize },
+
+	/// A length was too large to be written as a length prefix of
+	/// `prefix_size` bytes.
+	#[error("length {length} is too large for a {prefix_size}-byte length prefix")]
+	LengthTooLarge { length: usize, prefix_size: usize },
+
 	#[error("{0}")]
 	Other(Box<dyn Error>),
 }
 
 pub mod derive {
-	pub use cornflakes_datasize_macro::{DataSize, StaticDataSize};
+	pub use cornflakes_datasize_macro::{
+		ContextualReadable, DataSize, Flag, Readable, Sentinel, StaticDataSize, Wrapper, Writable,
+	};
 }
 
+mod bitmask;
+mod byte_order;
 mod datasize;
+mod field_path;
+mod limits;
+mod number;
+mod option;
+mod padding;
+mod prefixed;
 mod readable;
+mod string;
 mod writable;
 
 pub trait DataSize {
 	/// Returns the size of `self` in bytes when written with [`Writable`].
 	fn data_size(&self) -> usize;
+
+	/// Returns the size of every value of `Self`, if it is known without a
+	/// value.
+	///
+	/// This must be `Some(Self::SIZE)` for types which implement
+	/// [`StaticDataSize`], and allows generic types to use a static size when
+	/// their type parameters have one (for example, a derived `struct` whose
+	/// fields all have one). It is `None` by default, and must be `None` if
+	/// values can differ in size.
+	#[must_use]
+	fn static_data_size_hint() -> Option<usize>
+	where
+		Self: Sized,
+	{
+		None
+	}
 }
 
+/// A type whose values are all the same size.
+///
+/// Types implementing `StaticDataSize` must also return `Some(Self::SIZE)`
+/// from [`DataSize::static_data_size_hint`]: generic types which can't require
+/// `StaticDataSize`, like [`Option<T>`] and [`Vec<T>`], are sized with the
+/// hint. The derives and [`Wrapper`] types do this automatically.
 pub trait StaticDataSize: DataSize {
+	/// The size of `Self` in bytes when written with [`Writable`].
+	///
+	/// If `Self` is an `enum`, then the size is the size of its discriminant
+	/// plus the maximum size of the values contained in the variants
+	///
+	/// As this is a constant, it can be used in array lengths and `const`
+	/// assertions:
+	/// ```
+	/// use cornflakes::StaticDataSize;
+	///
+	/// let buffer = [0u8; <u32 as StaticDataSize>::SIZE];
+	/// const _: () = assert!(u16::SIZE == 2);
+	/// ```
+	const SIZE: usize;
+
 	/// Returns the size of `Self` in bytes when written with [`Writable`].
 	///
-	/// If `Self` is an `enum`, then the size is the maximum size of the values
-	/// contained in the variants
+	/// This is the same as [`SIZE`](Self::SIZE).
+	#[must_use]
 	fn static_data_size() -> usize
 	where
-		Self: Sized;
+		Self: Sized,
+	{
+		Self::SIZE
+	}
 }
 
 /// Reads a type from bytes.
 pub trait Readable: DataSize {
 	/// Reads [`Self`] from a [`Buf`] of bytes.
-	fn read_from(reader: &mut impl Buf) -> ReadResult<Self>
+	///
+	/// # Errors
+	/// Returns a [`ReadError`] if the bytes do not represent a valid value of
+	/// [`Self`].
+	fn read_from(reader: &mut impl OrderedBuf) -> ReadResult<Self>
 	where
 		Self: Sized;
+
+	/// Reads a list of `len` values of [`Self`] from a [`Buf`] of bytes.
+	///
+	/// This is used to read lists such as [`Vec<T>`] and `[T; N]`. By default,
+	/// each value is read with [`read_from`](Self::read_from) in turn, but
+	/// numbers override this to check the bytes remaining once and copy the
+	/// whole list at once, reversing the bytes of each value afterwards if the
+	/// reader's [`ByteOrder`] isn't native.
+	///
+	/// # Errors
+	/// Returns a [`ReadError`] if the bytes do not represent `len` valid values
+	/// of [`Self`], recording the index of the value which could not be read.
+	fn read_list(reader: &mut impl OrderedBuf, len: usize) -> ReadResult<Vec<Self>>
+	where
+		Self: Sized,
+	{
+		let mut list = Vec::with_capacity(readable::list_capacity::<Self>(reader, len));
+		let start = reader.remaining();
+
+		for index in 0..len {
+			let offset = start - reader.remaining();
+			list.push(Self::read_from(reader).map_err(|error| error.at_index(index, offset))?);
+		}
+
+		Ok(list)
+	}
+
+	/// Reads a value of [`Self`] into every element of `list` from a [`Buf`]
+	/// of bytes.
+	///
+	/// This is used to read lists in place, such as `[T; N]`. By default, each
+	/// value is read with [`read_from`](Self::read_from) in turn, but numbers
+	/// override this to check the bytes remaining once and copy the whole list
+	/// at once.
+	///
+	/// Arrays rely on every element being initialized when this returns `Ok`,
+	/// so it is sealed: it can only be overridden within this crate, as the
+	/// [`Sealed`](readable::Sealed) token can't be named outside of it.
+	///
+	/// # Errors
+	/// Returns a [`ReadError`] if the bytes do not represent a valid value of
+	/// [`Self`] for every element, recording the index of the value which
+	/// could not be read. If an error is returned, no elements of `list` are
+	/// left initialized.
+	#[doc(hidden)]
+	fn read_into(
+		reader: &mut impl OrderedBuf,
+		list: &mut [MaybeUninit<Self>],
+		_: readable::Sealed,
+	) -> ReadResult<()>
+	where
+		Self: Sized,
+	{
+		readable::read_each(reader, list)
+	}
 }
 
 /// Allows the reading of a type from bytes given some additional
@@ -90,7 +351,11 @@ pub trait ContextualReadable: DataSize {
 
 	/// Reads [`Self`] from a [`Buf`] of bytes, given some additional
 	/// [`Context`](Self::Context).
-	fn read_with(reader: &mut impl Buf, context: &Self::Context) -> ReadResult<Self>
+	///
+	/// # Errors
+	/// Returns a [`ReadError`] if the bytes do not represent a valid value of
+	/// [`Self`].
+	fn read_with(reader: &mut impl OrderedBuf, context: &Self::Context) -> ReadResult<Self>
 	where
 		Self: Sized;
 }
@@ -98,10 +363,72 @@ pub trait ContextualReadable: DataSize {
 /// Allows a type to be written as bytes.
 pub trait Writable: DataSize {
 	/// Writes [`self`](Self) as bytes to a [`BufMut`].
-	fn write_to(&self, writer: &mut impl BufMut) -> WriteResult;
+	///
+	/// # Errors
+	/// Returns a [`WriteError`] if [`self`](Self) could not be written.
+	fn write_to(&self, writer: &mut impl OrderedBufMut) -> WriteResult;
+
+	/// Writes a list of values of [`Self`] as bytes to a [`BufMut`].
+	///
+	/// This is used to write lists such as [`Vec<T>`] and `[T; N]`. By
+	/// default, each value is written with [`write_to`](Self::write_to) in
+	/// turn, but numbers override this to check the capacity remaining once
+	/// and copy the whole list at once, reversing the bytes of each value in
+	/// chunks first if the writer's [`ByteOrder`] isn't native.
+	///
+	/// # Errors
+	/// Returns a [`WriteError`] if the list could not be written.
+	fn write_list(list: &[Self], writer: &mut impl OrderedBufMut) -> WriteResult
+	where
+		Self: Sized,
+	{
+		for value in list {
+			value.write_to(writer)?;
+		}
+
+		Ok(())
+	}
+}
+
+/// An object-safe counterpart to [`Writable`], which writes to a
+/// `&mut dyn OrderedBufMut`.
+///
+/// [`Writable::write_to`] is generic over its writer, so `dyn Writable` isn't
+/// possible. This is implemented for every [`Writable`] type, so values of
+/// different types can be written through `dyn DynWritable`, or through a
+/// `dyn` trait which has [`DynWritable`] as a supertrait:
+/// ```
+/// use cornflakes::DynWritable;
+///
+/// let values: Vec<Box<dyn DynWritable>> = vec![Box::new(1u8), Box::new(2u16)];
+/// let mut bytes = Vec::new();
+///
+/// for value in &values {
+///     value.write_dyn(&mut bytes).unwrap();
+/// }
+///
+/// assert_eq!(bytes, [1, 0, 2]);
+/// ```
+pub trait DynWritable: DataSize {
+	/// Writes [`self`](Self) as bytes to a [`BufMut`].
+	///
+	/// # Errors
+	/// Returns a [`WriteError`] if [`self`](Self) could not be written.
+	fn write_dyn(&self, writer: &mut dyn OrderedBufMut) -> WriteResult;
+}
+
+impl<T: Writable> DynWritable for T {
+	fn write_dyn(&self, mut writer: &mut dyn OrderedBufMut) -> WriteResult {
+		self.write_to(&mut writer)
+	}
 }
 
-// TODO: see if this is actually a good way to do things
+/// A newtype which wraps a single value, such as a resource ID like
+/// `struct Window(u32)`.
+///
+/// This can be derived for tuple structs with a single field, which also
+/// derives [`DataSize`], [`StaticDataSize`], [`Readable`] and [`Writable`] the
+/// same as those of the wrapped type.
 pub trait Wrapper: DataSize {
 	type WrappedType: Writable + Readable + DataSize + StaticDataSize;
 
@@ -109,44 +436,72 @@ pub trait Wrapper: DataSize {
 	fn unwrap(&self) -> &Self::WrappedType;
 }
 
-impl<T: Wrapper> Readable for Option<T>
-where
-	T::WrappedType: Zero,
-{
-	fn read_from(buf: &mut impl Buf) -> ReadResult<Self>
+/// A type with a special [`SENTINEL`](Self::SENTINEL) value, which represents
+/// the absence of a value.
+///
+/// For example, a window of `0` in X11 often means `None`, and a key of `0`
+/// means `AnyKey`.
+///
+/// A sentinel has a static size so that `None` is the same size as any other
+/// value.
+pub trait Sentinel: StaticDataSize + Readable + Writable + PartialEq {
+	/// The value which represents the absence of a value.
+	const SENTINEL: Self;
+}
+
+/// Reads an [`Option`] with a [`Sentinel`] value: `None` if the value read is
+/// [`T::SENTINEL`](Sentinel::SENTINEL), and `Some` otherwise.
+///
+/// See [`Flagged`] and [`Omittable`] for other encodings of optional values.
+impl<T: Sentinel> Readable for Option<T> {
+	fn read_from(buf: &mut impl OrderedBuf) -> ReadResult<Self>
 	where
 		Self: Sized,
 	{
-		Ok(match T::WrappedType::read_from(buf)? {
-			x if x.is_zero() => None,
-			val => Some(T::wrap(val)),
+		debug_assert_size_hint::<T>();
+
+		Ok(match T::read_from(buf)? {
+			x if x == T::SENTINEL => None,
+			val => Some(val),
 		})
 	}
 }
 
-impl<T: Wrapper> Writable for Option<T>
-where
-	T::WrappedType: Zero,
-{
-	fn write_to(&self, buf: &mut impl BufMut) -> WriteResult {
+/// Writes `None` as [`T::SENTINEL`](Sentinel::SENTINEL).
+impl<T: Sentinel> Writable for Option<T> {
+	fn write_to(&self, buf: &mut impl OrderedBufMut) -> WriteResult {
+		debug_assert_size_hint::<T>();
+
 		match self {
-			None => T::WrappedType::zero().write_to(buf)?,
-			Some(val) => val.unwrap().write_to(buf)?,
+			None => T::SENTINEL.write_to(buf)?,
+			Some(val) => val.write_to(buf)?,
 		}
 
 		Ok(())
 	}
 }
 
+/// Asserts that `T`'s [`static_data_size_hint`](DataSize::static_data_size_hint)
+/// is its [`SIZE`](StaticDataSize::SIZE).
+///
+/// [`Option<T>`] is sized with the hint, so it would have a different size to
+/// what is read and written if the hint were forgotten.
+fn debug_assert_size_hint<T: StaticDataSize>() {
+	debug_assert_eq!(
+		T::static_data_size_hint(),
+		Some(T::SIZE),
+		"`static_data_size_hint` must return `Some(SIZE)` for types implementing `StaticDataSize`",
+	);
+}
+
 // This function is unused, but writing it here asserts that these traits are
 // _object safe_; that is, that the Rust compiler will generate an error if any
 // of these traits are accidentally made _object unsafe_, which means that they
 // cannot be used with the `dyn` keyword.
 fn _assert_object_safety(
 	_data_size: &dyn DataSize,
-	_static_data_size: &dyn StaticDataSize,
 	_readable: &dyn Readable,
 	_contextual_readable: &dyn ContextualReadable<Context = ()>,
-	//_writable: &dyn Writable,
+	_dyn_writable: &dyn DynWritable,
 ) {
 }
@@ -0,0 +1,307 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use crate::{readable, ContextualReadable, OrderedBuf, ReadError, ReadResult, Readable};
+use bytes::Buf;
+use std::cell::Cell;
+
+/// Limits on the lists read from untrusted input.
+///
+/// The length of a list is usually read from the input itself, so a malicious
+/// peer could claim that a list has billions of elements. Reading with
+/// [`Limits::read_from`] or [`Limits::read_with`] applies these limits to
+/// every list read from the input, such as [`Vec<T>`](Vec) and [`String`]
+/// fields of derived types and [`Prefixed`](crate::Prefixed) lists, which
+/// rejects lists that are too long before anything is allocated for them.
+///
+/// # Examples
+/// ```
+/// use cornflakes::{Limits, Prefixed, ReadError};
+///
+/// let limits = Limits::new(1024, 4096);
+///
+/// // A list of 2000 `u32`s is longer than 1024 elements.
+/// let mut reader: &[u8] = &[0x07, 0xd0, 0, 0, 0, 0];
+/// assert!(matches!(
+///     limits.read_from::<Prefixed<u16, Vec<u32>>>(&mut reader),
+///     Err(ReadError::LengthLimitExceeded { length: 2000, max: 1024 }),
+/// ));
+///
+/// let mut reader: &[u8] = &[0, 2, 0, 0, 0, 1, 0, 0, 0, 2];
+/// let list = limits.read_from::<Prefixed<u16, Vec<u32>>>(&mut reader).unwrap();
+/// assert_eq!(*list, [1, 2]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Limits {
+	max_length: usize,
+	max_size: usize,
+}
+
+thread_local! {
+	/// The limits which apply to the lists being read on this thread.
+	static CURRENT: Cell<Limits> = const { Cell::new(Limits::UNLIMITED) };
+}
+
+/// Restores the limits which applied before a read when dropped, even if the
+/// read panics.
+struct Restore(Limits);
+
+impl Drop for Restore {
+	fn drop(&mut self) {
+		CURRENT.with(|current| current.set(self.0));
+	}
+}
+
+impl Limits {
+	/// No limits at all, other than the number of bytes remaining in the
+	/// input.
+	pub const UNLIMITED: Self = Self::new(usize::MAX, usize::MAX);
+
+	/// Creates limits allowing lists of at most `max_length` elements and
+	/// `max_size` bytes.
+	#[must_use]
+	pub const fn new(max_length: usize, max_size: usize) -> Self {
+		Self {
+			max_length,
+			max_size,
+		}
+	}
+
+	/// The maximum number of elements in a list.
+	#[must_use]
+	pub const fn max_length(&self) -> usize {
+		self.max_length
+	}
+
+	/// The maximum size of a list, in bytes.
+	#[must_use]
+	pub const fn max_size(&self) -> usize {
+		self.max_size
+	}
+
+	/// Returns the limits which apply to the lists being read on this thread.
+	///
+	/// These are [`UNLIMITED`](Self::UNLIMITED) unless a value is being read
+	/// with [`Limits::read_from`], [`Limits::read_with`] or
+	/// [`Limits::read_list`].
+	#[must_use]
+	pub fn current() -> Self {
+		CURRENT.with(Cell::get)
+	}
+
+	/// Calls `read` with these limits applying to the lists it reads.
+	fn apply<R>(&self, read: impl FnOnce() -> R) -> R {
+		let _restore = Restore(CURRENT.with(|current| current.replace(*self)));
+
+		read()
+	}
+
+	/// Reads a `T`, applying these limits to every list read as part of it.
+	///
+	/// # Errors
+	/// Returns [`ReadError::LengthLimitExceeded`] or
+	/// [`ReadError::SizeLimitExceeded`] for a list which is outside these
+	/// limits, recording where that list is in `T`. Otherwise, returns any
+	/// error from reading `T`.
+	pub fn read_from<T: Readable>(&self, reader: &mut impl OrderedBuf) -> ReadResult<T> {
+		self.apply(|| T::read_from(reader))
+	}
+
+	/// Reads a `T` with the given `context`, applying these limits to every
+	/// list read as part of it.
+	///
+	/// # Errors
+	/// Returns [`ReadError::LengthLimitExceeded`] or
+	/// [`ReadError::SizeLimitExceeded`] for a list which is outside these
+	/// limits, recording where that list is in `T`. Otherwise, returns any
+	/// error from reading `T`.
+	pub fn read_with<T: ContextualReadable>(
+		&self,
+		reader: &mut impl OrderedBuf,
+		context: &T::Context,
+	) -> ReadResult<T> {
+		self.apply(|| T::read_with(reader, context))
+	}
+
+	/// Reads a list of `len` values of `T`, as [`Readable::read_list`] does,
+	/// applying these limits to it and to every list read as part of it.
+	///
+	/// If `T` has a static size, the size of the list is checked before it is
+	/// read. Otherwise, the sizes of its elements aren't known until they have
+	/// been read, so the size of the list is checked after each element.
+	///
+	/// # Errors
+	/// Returns [`ReadError::LengthLimitExceeded`] if `len` is more than
+	/// [`max_length`](Self::max_length), and [`ReadError::SizeLimitExceeded`]
+	/// if the list is larger than [`max_size`](Self::max_size). Otherwise,
+	/// returns any error from reading the list.
+	pub fn read_list<T: Readable>(
+		&self,
+		reader: &mut impl OrderedBuf,
+		len: usize,
+	) -> ReadResult<Vec<T>> {
+		self.apply(|| read_list(reader, len))
+	}
+
+	/// Checks that a list of `len` elements, which are each `size` bytes if
+	/// that is known, is within these limits.
+	///
+	/// Types which read a list whose length comes from the input should check
+	/// it against [`Limits::current`] before allocating anything for it.
+	///
+	/// Elements which take up no bytes are counted as one byte each: reading
+	/// them doesn't consume the input, so nothing else stops a list of
+	/// billions of them from being read.
+	///
+	/// # Errors
+	/// Returns [`ReadError::LengthLimitExceeded`] if `len` is more than
+	/// [`max_length`](Self::max_length), and [`ReadError::SizeLimitExceeded`]
+	/// if the list is larger than [`max_size`](Self::max_size).
+	pub fn check_list(&self, len: usize, size: Option<usize>) -> ReadResult<()> {
+		if len > self.max_length {
+			return Err(ReadError::LengthLimitExceeded {
+				length: len,
+				max: self.max_length,
+			});
+		}
+
+		if size.is_some_and(|size| len.saturating_mul(size.max(1)) > self.max_size) {
+			return Err(ReadError::SizeLimitExceeded { max: self.max_size });
+		}
+
+		Ok(())
+	}
+}
+
+/// Reads a list of `len` values of `T` within the [current](Limits::current)
+/// limits.
+pub fn read_list<T: Readable>(reader: &mut impl OrderedBuf, len: usize) -> ReadResult<Vec<T>> {
+	let limits = Limits::current();
+	let size = T::static_data_size_hint();
+
+	limits.check_list(len, size)?;
+
+	if size.is_some() || limits.max_size == usize::MAX {
+		return T::read_list(reader, len);
+	}
+
+	let mut list = Vec::with_capacity(readable::list_capacity::<T>(reader, len));
+	let start = reader.remaining();
+
+	for index in 0..len {
+		let offset = start - reader.remaining();
+		list.push(T::read_from(reader).map_err(|error| error.at_index(index, offset))?);
+
+		if start - reader.remaining() > limits.max_size {
+			return Err(ReadError::SizeLimitExceeded {
+				max: limits.max_size,
+			}
+			.at_index(index, offset));
+		}
+	}
+
+	Ok(list)
+}
+
+impl Default for Limits {
+	fn default() -> Self {
+		Self::UNLIMITED
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::Limits;
+	use crate::{Prefixed, ReadError, Readable};
+	use bytes::Bytes;
+
+	#[test]
+	fn test_limits_length() {
+		let limits = Limits::new(2, usize::MAX);
+
+		let mut reader: &[u8] = &[1, 2, 3];
+		assert!(matches!(
+			limits.read_list::<u8>(&mut reader, 3),
+			Err(ReadError::LengthLimitExceeded { length: 3, max: 2 }),
+		));
+		// Nothing is read.
+		assert_eq!(reader.len(), 3);
+
+		assert_eq!(limits.read_list::<u8>(&mut reader, 2).unwrap(), [1, 2]);
+	}
+
+	#[test]
+	fn test_limits_static_size() {
+		let limits = Limits::new(usize::MAX, 8);
+
+		// Billions of elements are rejected without reading or allocating.
+		let mut reader: &[u8] = &[0; 12];
+		assert!(matches!(
+			limits.read_list::<u32>(&mut reader, 4_000_000_000),
+			Err(ReadError::SizeLimitExceeded { max: 8 }),
+		));
+		assert_eq!(reader.len(), 12);
+
+		assert!(matches!(
+			limits.read_list::<u32>(&mut reader, 3),
+			Err(ReadError::SizeLimitExceeded { max: 8 }),
+		));
+		assert_eq!(limits.read_list::<u32>(&mut reader, 2).unwrap(), [0, 0]);
+	}
+
+	#[test]
+	fn test_limits_dynamic_size() {
+		let limits = Limits::new(usize::MAX, 4);
+
+		// Each string is one length byte followed by its bytes.
+		let bytes: &[u8] = &[1, b'a', 2, b'b', b'c', 0];
+
+		let mut reader = bytes;
+		let error = limits
+			.read_list::<Prefixed<u8, String>>(&mut reader, 3)
+			.unwrap_err();
+		assert_eq!(
+			error.to_string(),
+			"failed to read `[1]` at byte 2: list is larger than the maximum size of 4 bytes",
+		);
+
+		let mut reader = bytes;
+		let list = Limits::UNLIMITED
+			.read_list::<Prefixed<u8, String>>(&mut reader, 3)
+			.unwrap();
+		assert_eq!(list[1].as_str(), "bc");
+	}
+
+	#[test]
+	fn test_limits_prefixed() {
+		let limits = Limits::new(2, usize::MAX);
+
+		let mut reader: &[u8] = &[3, 1, 2, 3];
+		assert!(matches!(
+			limits.read_from::<Prefixed<u8, Vec<u8>>>(&mut reader),
+			Err(ReadError::LengthLimitExceeded { length: 3, max: 2 }),
+		));
+		assert!(matches!(
+			limits.read_from::<Prefixed<u8, Bytes>>(&mut &[3, 1, 2, 3][..]),
+			Err(ReadError::LengthLimitExceeded { length: 3, max: 2 }),
+		));
+
+		// Each list within a list is limited too.
+		let mut reader: &[u8] = &[2, 1, 1, 3, 1, 2, 3];
+		let error = limits
+			.read_from::<Prefixed<u8, Vec<Prefixed<u8, Vec<u8>>>>>(&mut reader)
+			.unwrap_err();
+		assert_eq!(
+			error.to_string(),
+			"failed to read `[1]` at byte 2: list length 3 exceeds the maximum length of 2",
+		);
+
+		// The limits only apply while reading with them.
+		let mut reader: &[u8] = &[3, 1, 2, 3];
+		assert_eq!(
+			*Prefixed::<u8, Vec<u8>>::read_from(&mut reader).unwrap(),
+			[1, 2, 3]
+		);
+	}
+}
@@ -0,0 +1,199 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use crate::{ByteOrder, OrderedBuf, OrderedBufMut};
+use bytes::Buf;
+use std::mem::{self, MaybeUninit};
+use std::{ptr, slice};
+
+/// A primitive number, which is read and written in the [`ByteOrder`] of the
+/// buffer.
+///
+/// # Safety
+/// Lists of numbers are copied as bytes, so a number must have no padding,
+/// and every pattern of bytes must be a valid number.
+pub unsafe trait Number: Copy {
+	/// A number to fill buffers with before they are overwritten.
+	const ZERO: Self;
+
+	/// Reads a number from `reader`, in `reader`'s byte order.
+	fn get(reader: &mut impl OrderedBuf) -> Self;
+
+	/// Writes the number to `writer`, in `writer`'s byte order.
+	fn put(self, writer: &mut impl OrderedBufMut);
+
+	/// Reverses the order of the number's bytes.
+	#[must_use]
+	fn swap_bytes(self) -> Self;
+}
+
+macro_rules! numbers {
+	(
+		integers: $($int:ty => $get_int:ident, $get_int_le:ident, $put_int:ident, $put_int_le:ident),*;
+		floats: $($float:ty => $get_float:ident, $get_float_le:ident, $put_float:ident, $put_float_le:ident),*$(;)?
+	) => {
+		$(
+			// SAFETY: integers have no padding, and any bytes are a valid integer.
+			unsafe impl Number for $int {
+				const ZERO: Self = 0;
+
+				fn get(reader: &mut impl OrderedBuf) -> Self {
+					match reader.byte_order().is_big_endian() {
+						true => reader.$get_int(),
+						false => reader.$get_int_le(),
+					}
+				}
+
+				fn put(self, writer: &mut impl OrderedBufMut) {
+					match writer.byte_order().is_big_endian() {
+						true => writer.$put_int(self),
+						false => writer.$put_int_le(self),
+					}
+				}
+
+				fn swap_bytes(self) -> Self {
+					self.swap_bytes()
+				}
+			}
+		)*
+
+		$(
+			// SAFETY: floats have no padding, and any bytes are a valid float.
+			unsafe impl Number for $float {
+				const ZERO: Self = 0.0;
+
+				fn get(reader: &mut impl OrderedBuf) -> Self {
+					match reader.byte_order().is_big_endian() {
+						true => reader.$get_float(),
+						false => reader.$get_float_le(),
+					}
+				}
+
+				fn put(self, writer: &mut impl OrderedBufMut) {
+					match writer.byte_order().is_big_endian() {
+						true => writer.$put_float(self),
+						false => writer.$put_float_le(self),
+					}
+				}
+
+				fn swap_bytes(self) -> Self {
+					Self::from_bits(self.to_bits().swap_bytes())
+				}
+			}
+		)*
+	};
+}
+
+numbers! {
+	integers:
+		u8 => get_u8, get_u8, put_u8, put_u8,
+		u16 => get_u16, get_u16_le, put_u16, put_u16_le,
+		u32 => get_u32, get_u32_le, put_u32, put_u32_le,
+		u64 => get_u64, get_u64_le, put_u64, put_u64_le,
+		u128 => get_u128, get_u128_le, put_u128, put_u128_le,
+
+		i8 => get_i8, get_i8, put_i8, put_i8,
+		i16 => get_i16, get_i16_le, put_i16, put_i16_le,
+		i32 => get_i32, get_i32_le, put_i32, put_i32_le,
+		i64 => get_i64, get_i64_le, put_i64, put_i64_le,
+		i128 => get_i128, get_i128_le, put_i128, put_i128_le;
+
+	floats:
+		f32 => get_f32, get_f32_le, put_f32, put_f32_le,
+		f64 => get_f64, get_f64_le, put_f64, put_f64_le;
+}
+
+/// The number of numbers which are converted to another byte order at once
+/// when writing a list.
+const CHUNK_LEN: usize = 64;
+
+/// Returns whether numbers' bytes must be reversed to convert between the
+/// native byte order and `byte_order`.
+const fn swaps<T>(byte_order: ByteOrder) -> bool {
+	mem::size_of::<T>() > 1 && byte_order.is_big_endian() != cfg!(target_endian = "big")
+}
+
+/// Returns the bytes of `list` in the native byte order.
+const fn as_bytes<T: Number>(list: &[T]) -> &[u8] {
+	// SAFETY: numbers have no padding, so every byte of `list` is initialized.
+	unsafe { slice::from_raw_parts(list.as_ptr().cast::<u8>(), mem::size_of_val(list)) }
+}
+
+/// Reads a number into every element of `list`, once `reader` is known to have
+/// enough bytes remaining.
+///
+/// The bytes of the whole list are copied at once, then each number's bytes
+/// are reversed in place if the reader's byte order isn't native.
+pub fn read_list<T: Number>(reader: &mut impl OrderedBuf, list: &mut [MaybeUninit<T>]) {
+	let swap = swaps::<T>(reader.byte_order());
+
+	// SAFETY: `list` is `size_of_val(list)` bytes long, and any bytes are
+	// valid for `MaybeUninit<u8>`.
+	let bytes = unsafe {
+		slice::from_raw_parts_mut(
+			list.as_mut_ptr().cast::<MaybeUninit<u8>>(),
+			mem::size_of_val(list),
+		)
+	};
+	copy_to_uninit(reader, bytes);
+
+	if swap {
+		// SAFETY: every number was initialized when its bytes were copied, as
+		// any bytes are a valid number.
+		let list = unsafe { slice::from_raw_parts_mut(list.as_mut_ptr().cast::<T>(), list.len()) };
+
+		for number in list {
+			*number = number.swap_bytes();
+		}
+	}
+}
+
+/// Writes every number in `list`, once `writer` is known to have enough
+/// capacity remaining.
+///
+/// If the writer's byte order is native, the bytes of the whole list are
+/// copied at once. Otherwise, the numbers are converted in chunks of
+/// [`CHUNK_LEN`], and each chunk is copied at once.
+pub fn write_list<T: Number>(list: &[T], writer: &mut impl OrderedBufMut) {
+	if !swaps::<T>(writer.byte_order()) {
+		writer.put_slice(as_bytes(list));
+		return;
+	}
+
+	let mut buffer = [T::ZERO; CHUNK_LEN];
+
+	for numbers in list.chunks(CHUNK_LEN) {
+		let swapped = &mut buffer[..numbers.len()];
+
+		for (swapped, number) in swapped.iter_mut().zip(numbers) {
+			*swapped = number.swap_bytes();
+		}
+
+		writer.put_slice(as_bytes(swapped));
+	}
+}
+
+/// Copies bytes from `reader` into every element of `bytes`, once `reader` is
+/// known to have enough bytes remaining.
+fn copy_to_uninit(reader: &mut impl Buf, bytes: &mut [MaybeUninit<u8>]) {
+	let mut copied = 0;
+
+	while copied < bytes.len() {
+		let chunk = reader.chunk();
+		let len = chunk.len().min(bytes.len() - copied);
+
+		// SAFETY: `chunk` has at least `len` bytes, and so does
+		// `bytes[copied..]`. `bytes` is borrowed mutably, so they don't overlap.
+		unsafe {
+			ptr::copy_nonoverlapping(
+				chunk.as_ptr(),
+				bytes[copied..].as_mut_ptr().cast::<u8>(),
+				len,
+			);
+		}
+
+		reader.advance(len);
+		copied += len;
+	}
+}
@@ -0,0 +1,133 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! [`Option`] encodings.
+//!
+//! An [`Option<T>`] itself is encoded with a sentinel value: `None` is written
+//! as [`T::SENTINEL`](crate::Sentinel::SENTINEL).
+//! [`Flagged`] and [`Omittable`] are the other encodings.
+
+use crate::{
+	ensure_capacity, DataSize, OrderedBuf, OrderedBufMut, ReadResult, Readable, Writable,
+	WriteResult,
+};
+use bytes::Buf;
+
+/// An optional value which is preceded by a presence byte.
+///
+/// The presence byte is `1` if the value is present, in which case it is
+/// followed by the value, and `0` if it is not. When read, any nonzero
+/// presence byte means that the value is present.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Flagged<T>(pub Option<T>);
+
+impl<T> From<Option<T>> for Flagged<T> {
+	fn from(value: Option<T>) -> Self {
+		Self(value)
+	}
+}
+
+impl<T: DataSize> DataSize for Flagged<T> {
+	fn data_size(&self) -> usize {
+		1 + self.0.as_ref().map_or(0, DataSize::data_size)
+	}
+}
+
+impl<T: Readable> Readable for Flagged<T> {
+	fn read_from(reader: &mut impl OrderedBuf) -> ReadResult<Self> {
+		Ok(Self(match u8::read_from(reader)? {
+			0 => None,
+			_ => Some(T::read_from(reader)?),
+		}))
+	}
+}
+
+impl<T: Writable> Writable for Flagged<T> {
+	fn write_to(&self, writer: &mut impl OrderedBufMut) -> WriteResult {
+		ensure_capacity(writer, self.data_size())?;
+
+		match &self.0 {
+			None => writer.put_u8(0),
+
+			Some(value) => {
+				writer.put_u8(1);
+				value.write_to(writer)?;
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// An optional value which is omitted entirely when it is absent.
+///
+/// As nothing is written for `None`, an [`Omittable`] value must come at the
+/// end of the bytes being read: it is read as `None` if no bytes remain, and
+/// otherwise as `Some`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Omittable<T>(pub Option<T>);
+
+impl<T> From<Option<T>> for Omittable<T> {
+	fn from(value: Option<T>) -> Self {
+		Self(value)
+	}
+}
+
+impl<T: DataSize> DataSize for Omittable<T> {
+	fn data_size(&self) -> usize {
+		self.0.as_ref().map_or(0, DataSize::data_size)
+	}
+}
+
+impl<T: Readable> Readable for Omittable<T> {
+	fn read_from(reader: &mut impl OrderedBuf) -> ReadResult<Self> {
+		if !reader.has_remaining() {
+			return Ok(Self(None));
+		}
+
+		Ok(Self(Some(T::read_from(reader)?)))
+	}
+}
+
+impl<T: Writable> Writable for Omittable<T> {
+	fn write_to(&self, writer: &mut impl OrderedBufMut) -> WriteResult {
+		if let Some(value) = &self.0 {
+			value.write_to(writer)?;
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::{Flagged, Omittable};
+	use crate::{DataSize, Readable, Writable};
+
+	#[test]
+	fn test_flagged_round_trip() {
+		for (value, expected) in [(None, &[0][..]), (Some(0x0102u16), &[1, 1, 2][..])] {
+			let value = Flagged(value);
+			assert_eq!(value.data_size(), expected.len());
+
+			let mut bytes = Vec::new();
+			value.write_to(&mut bytes).unwrap();
+			assert_eq!(bytes, expected);
+			assert_eq!(Flagged::read_from(&mut &bytes[..]).unwrap(), value);
+		}
+	}
+
+	#[test]
+	fn test_omittable_round_trip() {
+		for (value, expected) in [(None, &[][..]), (Some(0x0102u16), &[1, 2][..])] {
+			let value = Omittable(value);
+			assert_eq!(value.data_size(), expected.len());
+
+			let mut bytes = Vec::new();
+			value.write_to(&mut bytes).unwrap();
+			assert_eq!(bytes, expected);
+			assert_eq!(Omittable::read_from(&mut &bytes[..]).unwrap(), value);
+		}
+	}
+}
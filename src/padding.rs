@@ -0,0 +1,85 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use crate::{
+	ensure_capacity, ensure_remaining, DataSize, OrderedBuf, OrderedBufMut, ReadResult, Readable,
+	StaticDataSize, Writable, WriteResult,
+};
+
+/// Returns the number of bytes of padding needed after `len` bytes to align
+/// them to a multiple of `align` bytes.
+///
+/// For example, X11 pads lists and strings to a multiple of 4 bytes with
+/// `pad(len, 4)`.
+///
+/// # Panics
+/// Panics if `align` is zero.
+#[must_use]
+pub const fn pad(len: usize, align: usize) -> usize {
+	(align - len % align) % align
+}
+
+/// `N` bytes of padding.
+///
+/// Padding is written as zeroes. When it is read, its bytes are skipped,
+/// whatever their values.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Pad<const N: usize>;
+
+impl<const N: usize> DataSize for Pad<N> {
+	fn data_size(&self) -> usize {
+		N
+	}
+
+	fn static_data_size_hint() -> Option<usize> {
+		Some(N)
+	}
+}
+
+impl<const N: usize> StaticDataSize for Pad<N> {
+	const SIZE: usize = N;
+}
+
+impl<const N: usize> Readable for Pad<N> {
+	fn read_from(reader: &mut impl OrderedBuf) -> ReadResult<Self> {
+		ensure_remaining(reader, N)?;
+		reader.advance(N);
+
+		Ok(Self)
+	}
+}
+
+impl<const N: usize> Writable for Pad<N> {
+	fn write_to(&self, writer: &mut impl OrderedBufMut) -> WriteResult {
+		ensure_capacity(writer, N)?;
+		writer.put_bytes(0, N);
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::{pad, Pad};
+	use crate::{Readable, Writable};
+
+	#[test]
+	fn test_pad() {
+		assert_eq!(pad(0, 4), 0);
+		assert_eq!(pad(1, 4), 3);
+		assert_eq!(pad(4, 4), 0);
+		assert_eq!(pad(7, 4), 1);
+	}
+
+	#[test]
+	fn test_pad_round_trip() {
+		let mut bytes = Vec::new();
+		Pad::<3>.write_to(&mut bytes).unwrap();
+		assert_eq!(bytes, [0, 0, 0]);
+
+		let mut reader: &[u8] = &[1, 2, 3, 4];
+		Pad::<3>::read_from(&mut reader).unwrap();
+		assert_eq!(reader, [4]);
+	}
+}
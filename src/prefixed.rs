@@ -0,0 +1,220 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use crate::{
+	ensure_capacity, ContextualReadable, DataSize, OrderedBuf, OrderedBufMut, ReadResult, Readable,
+	StaticDataSize, Writable, WriteError, WriteResult,
+};
+use bytes::Bytes;
+use num::{NumCast, ToPrimitive};
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+/// A value which is read with its length as its context, such as a [`String`]
+/// (whose length is in bytes) or a [`Vec<T>`] (whose length is its number of
+/// elements).
+pub trait Length: ContextualReadable<Context = usize> {
+	/// Returns the length of `self`, as given as context when it is read.
+	fn length(&self) -> usize;
+}
+
+impl Length for String {
+	fn length(&self) -> usize {
+		self.len()
+	}
+}
+
+impl Length for Bytes {
+	fn length(&self) -> usize {
+		self.len()
+	}
+}
+
+impl<T: Readable> Length for Vec<T> {
+	fn length(&self) -> usize {
+		self.len()
+	}
+}
+
+/// A value of type `T` which is preceded by its [length](Length) as an `L`.
+///
+/// For example, `Prefixed<u8, String>` is a string preceded by its length in
+/// bytes as a `u8`, and `Prefixed<u32, Vec<T>>` is a list preceded by its
+/// number of elements as a `u32`.
+///
+/// # Errors
+/// Writing a value whose length does not fit in `L` returns
+/// [`WriteError::LengthTooLarge`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Prefixed<L, T> {
+	value: T,
+	length: PhantomData<L>,
+}
+
+impl<L, T> Prefixed<L, T> {
+	pub const fn new(value: T) -> Self {
+		Self {
+			value,
+			length: PhantomData,
+		}
+	}
+
+	#[allow(clippy::missing_const_for_fn)] // `T` may need to be dropped.
+	pub fn into_inner(self) -> T {
+		self.value
+	}
+}
+
+impl<L, T> From<T> for Prefixed<L, T> {
+	fn from(value: T) -> Self {
+		Self::new(value)
+	}
+}
+
+impl<L, T> Deref for Prefixed<L, T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		&self.value
+	}
+}
+
+impl<L, T> DerefMut for Prefixed<L, T> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.value
+	}
+}
+
+impl<L: StaticDataSize, T: DataSize> DataSize for Prefixed<L, T> {
+	fn data_size(&self) -> usize {
+		L::SIZE + self.value.data_size()
+	}
+}
+
+impl<L, T> Readable for Prefixed<L, T>
+where
+	L: StaticDataSize + Readable + ToPrimitive,
+	T: Length,
+{
+	fn read_from(reader: &mut impl OrderedBuf) -> ReadResult<Self> {
+		// A length which doesn't fit in a `usize` can't be read anyway, so
+		// reading it will fail as normal with an unexpected end of input.
+		let length = L::read_from(reader)?.to_usize().unwrap_or(usize::MAX);
+
+		Ok(Self::new(T::read_with(reader, &length)?))
+	}
+}
+
+impl<L, T> Writable for Prefixed<L, T>
+where
+	L: StaticDataSize + Writable + NumCast,
+	T: Length + Writable,
+{
+	fn write_to(&self, writer: &mut impl OrderedBufMut) -> WriteResult {
+		let length = self.value.length();
+		let prefix: L = NumCast::from(length).ok_or(WriteError::LengthTooLarge {
+			length,
+			prefix_size: L::SIZE,
+		})?;
+
+		ensure_capacity(writer, self.data_size())?;
+
+		prefix.write_to(writer)?;
+		self.value.write_to(writer)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::Prefixed;
+	use crate::{DataSize, Latin1, Readable, Writable, WriteError};
+
+	#[test]
+	fn test_prefixed_string_round_trip() {
+		let mut bytes = Vec::new();
+		Prefixed::<u16, String>::new("abc".to_owned())
+			.write_to(&mut bytes)
+			.unwrap();
+		assert_eq!(bytes, [0, 3, b'a', b'b', b'c']);
+
+		let string = Prefixed::<u16, String>::read_from(&mut &bytes[..]).unwrap();
+		assert_eq!(*string, "abc");
+	}
+
+	#[test]
+	fn test_prefixed_vec_round_trip() {
+		let list = Prefixed::<u32, Vec<u16>>::new(vec![1, 2]);
+		assert_eq!(list.data_size(), 4 + 2 * 2);
+
+		let mut bytes = Vec::new();
+		list.write_to(&mut bytes).unwrap();
+		assert_eq!(bytes, [0, 0, 0, 2, 0, 1, 0, 2]);
+
+		let mut reader = &bytes[..];
+		assert_eq!(
+			Prefixed::<u32, Vec<u16>>::read_from(&mut reader).unwrap(),
+			list
+		);
+		assert!(reader.is_empty());
+	}
+
+	#[test]
+	fn test_prefixed_vec_eof() {
+		let error = Prefixed::<u8, Vec<u16>>::read_from(&mut &[2, 0, 1, 0][..]).unwrap_err();
+		assert_eq!(
+			error.to_string(),
+			"failed to read `[1]` at byte 2: unexpected end of input: needed 2 bytes, but only 1 \
+			 remain"
+		);
+	}
+
+	#[test]
+	fn test_write_vec_insufficient_capacity() {
+		let mut buf = [0xaa; 3];
+		let mut writer = &mut buf[..];
+		assert!(matches!(
+			Prefixed::<u8, Vec<u16>>::new(vec![1, 2]).write_to(&mut writer),
+			Err(WriteError::InsufficientCapacity {
+				required: 5,
+				remaining: 3
+			})
+		));
+
+		// Nothing is written if there isn't room for all of it.
+		assert_eq!(writer.len(), 3);
+		assert_eq!(buf, [0xaa; 3]);
+	}
+
+	#[test]
+	fn test_prefixed_vec_length_too_large() {
+		let error = Prefixed::<u8, Vec<u8>>::new(vec![0; 256])
+			.write_to(&mut Vec::new())
+			.unwrap_err();
+		assert!(matches!(
+			error,
+			WriteError::LengthTooLarge { length: 256, .. }
+		));
+	}
+
+	#[test]
+	fn test_prefixed_latin1() {
+		let string = Prefixed::<u8, Latin1>::read_from(&mut &[2, 0xe9, 0x61][..]).unwrap();
+		assert_eq!(string.0, "éa");
+	}
+
+	#[test]
+	fn test_prefixed_length_too_large() {
+		let error = Prefixed::<u8, String>::new("a".repeat(256))
+			.write_to(&mut Vec::new())
+			.unwrap_err();
+
+		assert!(matches!(
+			error,
+			WriteError::LengthTooLarge {
+				length: 256,
+				prefix_size: 1
+			}
+		));
+	}
+}
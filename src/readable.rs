@@ -2,80 +2,537 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
-use crate::{ContextualReadable, ReadResult, Readable};
-use bytes::Buf;
+use crate::number::{self, Number};
+use crate::{limits, ContextualReadable, Limits, OrderedBuf, ReadError, ReadResult, Readable};
+use bytes::{Buf, Bytes};
+use std::marker::PhantomData;
+use std::mem::{self, MaybeUninit};
+use std::ptr;
 
-macro_rules! implement {
-	($($reader:ident, $ty:ty => $expr:expr),*$(,)?) => {
+/// Checks that at least `needed` bytes remain in `reader`.
+///
+/// [`Buf`]'s methods panic if there are too few bytes remaining, so this
+/// should be called before reading from `reader` directly.
+///
+/// # Errors
+/// Returns [`ReadError::UnexpectedEof`] if fewer than `needed` bytes remain.
+pub fn ensure_remaining(reader: &impl Buf, needed: usize) -> ReadResult<()> {
+	let available = reader.remaining();
+
+	if available < needed {
+		return Err(ReadError::UnexpectedEof { needed, available });
+	}
+
+	Ok(())
+}
+
+/// A token which only this crate can name, sealing
+/// [`Readable::read_into`] so that it can't be overridden elsewhere.
+///
+/// Arrays trust `read_into` to initialize every element, which a safe
+/// implementation outside of this crate couldn't be relied upon to do.
+#[derive(Debug, Clone, Copy)]
+pub struct Sealed(());
+
+/// Returns the capacity to allocate up front for a list of `len` values of `T`
+/// read from `reader`.
+///
+/// `len` may come from untrusted input, so the capacity is limited to as many
+/// values as the bytes remaining in `reader` would take up in memory: reading
+/// a list never allocates more than the size of the input before its values
+/// have been read.
+pub fn list_capacity<T>(reader: &impl Buf, len: usize) -> usize {
+	len.min(reader.remaining() / mem::size_of::<T>().max(1))
+}
+
+/// Checks that a list of `len` values of `size` bytes each remains in
+/// `reader`.
+///
+/// If it doesn't, the error is the same as the error which reading the values
+/// one at a time would return: the value which runs out of bytes is recorded.
+fn ensure_list_remaining(reader: &impl Buf, len: usize, size: usize) -> ReadResult<()> {
+	let available = reader.remaining();
+	let index = available / size;
+
+	if index >= len {
+		return Ok(());
+	}
+
+	Err(ReadError::UnexpectedEof {
+		needed: size,
+		available: available % size,
+	}
+	.at_index(index, index * size))
+}
+
+// Lists of numbers are checked and read all at once, rather than checking the
+// number of bytes remaining for each value.
+macro_rules! numbers {
+	($($ty:ty),*$(,)?) => {
 		$(
-			impl $crate::Readable for $ty {
-				fn read_from($reader: &mut impl bytes::Buf) -> Result<Self, $crate::ReadError> {
-					Ok($expr)
+			impl Readable for $ty {
+				fn read_from(reader: &mut impl OrderedBuf) -> ReadResult<Self> {
+					ensure_remaining(reader, mem::size_of::<Self>())?;
+
+					Ok(Self::get(reader))
+				}
+
+				fn read_list(reader: &mut impl OrderedBuf, len: usize) -> ReadResult<Vec<Self>> {
+					// Check the length before allocating for it.
+					ensure_list_remaining(reader, len, mem::size_of::<Self>())?;
+
+					let mut list = Vec::with_capacity(len);
+					Self::read_into(reader, &mut list.spare_capacity_mut()[..len], Sealed(()))?;
+					// SAFETY: `read_into` initialized the first `len` elements.
+					unsafe { list.set_len(len) };
+
+					Ok(list)
+				}
+
+				fn read_into(
+					reader: &mut impl OrderedBuf,
+					list: &mut [MaybeUninit<Self>],
+					_: Sealed,
+				) -> ReadResult<()> {
+					ensure_list_remaining(reader, list.len(), mem::size_of::<Self>())?;
+					number::read_list(reader, list);
+
+					Ok(())
 				}
 			}
 		)*
 	};
 }
 
-implement! {
-	reader, i8 => reader.get_i8(),
-	reader, i16 => reader.get_i16(),
-	reader, i32 => reader.get_i32(),
-	reader, i64 => reader.get_i64(),
-	reader, i128 => reader.get_i128(),
+numbers!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+impl Readable for bool {
+	fn read_from(reader: &mut impl OrderedBuf) -> ReadResult<Self> {
+		ensure_remaining(reader, 1)?;
+
+		Ok(reader.get_u8() != 0)
+	}
+
+	fn read_list(reader: &mut impl OrderedBuf, len: usize) -> ReadResult<Vec<Self>> {
+		ensure_list_remaining(reader, len, 1)?;
+
+		Ok((0..len).map(|_| reader.get_u8() != 0).collect())
+	}
+
+	fn read_into(
+		reader: &mut impl OrderedBuf,
+		list: &mut [MaybeUninit<Self>],
+		_: Sealed,
+	) -> ReadResult<()> {
+		ensure_list_remaining(reader, list.len(), 1)?;
 
-	reader, u8 => reader.get_u8(),
-	reader, u16 => reader.get_u16(),
-	reader, u32 => reader.get_u32(),
-	reader, u64 => reader.get_u64(),
-	reader, u128 => reader.get_u128(),
+		for element in list {
+			element.write(reader.get_u8() != 0);
+		}
 
-	reader, f32 => reader.get_f32(),
-	reader, f64 => reader.get_f64(),
+		Ok(())
+	}
+}
 
-	reader, bool => reader.get_u8() != 0,
+/// A list which is being read in place, and the number of its elements which
+/// have been initialized so far.
+///
+/// If an element fails to be read, the elements which have already been read
+/// are dropped when this is dropped.
+struct PartialList<'a, T> {
+	list: &'a mut [MaybeUninit<T>],
+	initialized: usize,
 }
 
+impl<T> PartialList<'_, T> {
+	const fn push(&mut self, value: T) {
+		self.list[self.initialized].write(value);
+		self.initialized += 1;
+	}
+
+	fn finish(self) {
+		assert_eq!(
+			self.initialized,
+			self.list.len(),
+			"every element must be initialized"
+		);
+
+		// The elements are now owned by the caller, so they must not be dropped
+		// with `self`.
+		mem::forget(self);
+	}
+}
+
+impl<T> Drop for PartialList<'_, T> {
+	fn drop(&mut self) {
+		for element in &mut self.list[..self.initialized] {
+			// SAFETY: the first `initialized` elements have been initialized.
+			unsafe { element.assume_init_drop() };
+		}
+	}
+}
+
+/// Reads each element of `list` with [`Readable::read_from`] in turn.
+///
+/// This is the default implementation of [`Readable::read_into`].
+pub fn read_each<T: Readable>(
+	reader: &mut impl OrderedBuf,
+	list: &mut [MaybeUninit<T>],
+) -> ReadResult<()> {
+	let len = list.len();
+	let mut list = PartialList {
+		list,
+		initialized: 0,
+	};
+	let start = reader.remaining();
+
+	for index in 0..len {
+		let offset = start - reader.remaining();
+		list.push(T::read_from(reader).map_err(|error| error.at_index(index, offset))?);
+	}
+
+	list.finish();
+
+	Ok(())
+}
+
+/// Reads the elements in place, without allocating.
 impl<T: Readable, const N: usize> Readable for [T; N] {
-	fn read_from(reader: &mut impl Buf) -> ReadResult<Self>
+	fn read_from(reader: &mut impl OrderedBuf) -> ReadResult<Self>
 	where
 		Self: Sized,
 	{
-		let mut vec = Vec::new();
+		// An array of `MaybeUninit` doesn't need initializing.
+		let mut array = [const { MaybeUninit::uninit() }; N];
+		T::read_into(reader, &mut array, Sealed(()))?;
 
-		for _ in 0..N {
-			vec.push(T::read_from(reader)?);
-		}
-
-		Ok(match vec.try_into() {
-			Ok(array) => array,
-			Err(_) => unreachable!("we know the length of this vec is `N`"),
-		})
+		// SAFETY: `read_into` can only be implemented in this crate, where
+		// every implementation initializes every element on success, and
+		// `[MaybeUninit<T>; N]` has the same layout as `[T; N]`.
+		Ok(unsafe { ptr::read(ptr::addr_of!(array).cast::<Self>()) })
 	}
 }
 
 impl<T: Readable> Readable for Box<T> {
-	fn read_from(reader: &mut impl Buf) -> ReadResult<Self>
+	fn read_from(reader: &mut impl OrderedBuf) -> ReadResult<Self>
 	where
 		Self: Sized,
 	{
-		Ok(Box::new(T::read_from(reader)?))
+		Ok(Self::new(T::read_from(reader)?))
 	}
 }
 
+/// Reads a list of `context` values within the [current](Limits::current)
+/// limits.
 impl<T: Readable> ContextualReadable for Vec<T> {
 	type Context = usize;
 
-	fn read_with(reader: &mut impl Buf, context: &Self::Context) -> ReadResult<Self>
+	fn read_with(reader: &mut impl OrderedBuf, context: &Self::Context) -> ReadResult<Self>
 	where
 		Self: Sized,
 	{
-		let mut vec = Vec::new();
+		limits::read_list(reader, *context)
+	}
+}
+
+impl Readable for () {
+	fn read_from(_reader: &mut impl OrderedBuf) -> ReadResult<Self> {
+		Ok(())
+	}
+}
+
+// Tuples are read element by element, with each element's field in its path.
+macro_rules! tuples {
+	($(($($ty:ident $index:tt),+)),+$(,)?) => {
+		$(
+			impl<$($ty: Readable),+> Readable for ($($ty,)+) {
+				fn read_from(reader: &mut impl OrderedBuf) -> ReadResult<Self> {
+					let start = reader.remaining();
+
+					Ok(($(
+						{
+							let offset = start - reader.remaining();
+							$ty::read_from(reader)
+								.map_err(|error| error.in_tuple_field(stringify!($index), offset))?
+						},
+					)+))
+				}
+			}
+		)+
+	};
+}
+
+tuples! {
+	(A 0),
+	(A 0, B 1),
+	(A 0, B 1, C 2),
+	(A 0, B 1, C 2, D 3),
+	(A 0, B 1, C 2, D 3, E 4),
+	(A 0, B 1, C 2, D 3, E 4, F 5),
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6),
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7),
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8),
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9),
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10),
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11),
+}
 
-		for _ in 0..*context {
-			vec.push(T::read_from(reader)?);
+impl<T: ?Sized> Readable for PhantomData<T> {
+	fn read_from(_reader: &mut impl OrderedBuf) -> ReadResult<Self> {
+		Ok(Self)
+	}
+}
+
+/// Reads `context` bytes, within the [current](Limits::current) limits.
+///
+/// If the reader is itself [`Bytes`], this doesn't copy: the [`Bytes`] which is
+/// returned shares the reader's underlying memory.
+impl ContextualReadable for Bytes {
+	type Context = usize;
+
+	fn read_with(reader: &mut impl OrderedBuf, context: &Self::Context) -> ReadResult<Self> {
+		Limits::current().check_list(*context, Some(1))?;
+		ensure_remaining(reader, *context)?;
+
+		Ok(reader.copy_to_bytes(*context))
+	}
+}
+
+/// Reads every remaining byte.
+///
+/// Like [`Bytes::read_with`](ContextualReadable::read_with), this doesn't copy
+/// if the reader is itself [`Bytes`].
+impl Readable for Bytes {
+	fn read_from(reader: &mut impl OrderedBuf) -> ReadResult<Self> {
+		Ok(reader.copy_to_bytes(reader.remaining()))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::{ByteOrder, ByteOrdered, ContextualReadable, ReadError, Readable};
+	use bytes::{Buf, Bytes};
+
+	#[test]
+	fn test_read_primitive_eof() {
+		let mut bytes: &[u8] = &[1, 2, 3];
+		assert!(matches!(
+			u32::read_from(&mut bytes),
+			Err(ReadError::UnexpectedEof {
+				needed: 4,
+				available: 3
+			})
+		));
+	}
+
+	#[test]
+	fn test_read_array_eof() {
+		let mut bytes: &[u8] = &[0, 1, 0];
+		let error = <[u16; 2]>::read_from(&mut bytes).unwrap_err();
+
+		assert!(matches!(
+			error.root_cause(),
+			ReadError::UnexpectedEof {
+				needed: 2,
+				available: 1
+			}
+		));
+		assert!(matches!(
+			error,
+			ReadError::InField { ref path, offset: 2, .. } if path.to_string() == "[1]"
+		));
+	}
+
+	#[test]
+	fn test_read_vec_eof() {
+		let mut bytes: &[u8] = &[1, 2];
+		let error = Vec::<u8>::read_with(&mut bytes, &3).unwrap_err();
+
+		assert!(matches!(
+			error.root_cause(),
+			ReadError::UnexpectedEof {
+				needed: 1,
+				available: 0
+			}
+		));
+		assert!(matches!(
+			error,
+			ReadError::InField { ref path, offset: 2, .. } if path.to_string() == "[2]"
+		));
+	}
+
+	#[test]
+	fn test_read_box_eof() {
+		let mut bytes: &[u8] = &[];
+		assert!(matches!(
+			Box::<bool>::read_from(&mut bytes),
+			Err(ReadError::UnexpectedEof {
+				needed: 1,
+				available: 0
+			})
+		));
+	}
+
+	#[test]
+	fn test_read_tuple() {
+		let mut bytes: &[u8] = &[1, 0, 2, 3];
+		assert_eq!(<(u8, u16, ())>::read_from(&mut bytes).unwrap(), (1, 2, ()));
+
+		let error = <(u8, u16)>::read_from(&mut bytes).unwrap_err();
+		assert_eq!(
+			error.to_string(),
+			"failed to read `.1` at byte 1: unexpected end of input: needed 2 bytes, but only 0 \
+			 remain"
+		);
+
+		let mut bytes: &[u8] = &[1, 2, 0];
+		let error = <(u8, (u8, u16))>::read_from(&mut bytes).unwrap_err();
+		assert_eq!(
+			error.to_string(),
+			"failed to read `.1.1` at byte 2: unexpected end of input: needed 2 bytes, but only 1 \
+			 remain"
+		);
+	}
+
+	#[test]
+	fn test_read_bytes_zero_copy() {
+		let input = Bytes::from_static(&[1, 2, 3, 4, 5]);
+		let mut reader = input.clone();
+
+		let bytes = Bytes::read_with(&mut reader, &3).unwrap();
+		assert_eq!(bytes, [1, 2, 3][..]);
+		assert_eq!(bytes.as_ptr(), input.as_ptr());
+
+		let rest = Bytes::read_from(&mut reader).unwrap();
+		assert_eq!(rest, [4, 5][..]);
+		assert!(reader.is_empty());
+	}
+
+	#[test]
+	fn test_read_bytes_eof() {
+		let mut bytes: &[u8] = &[1, 2];
+		assert!(matches!(
+			Bytes::read_with(&mut bytes, &3),
+			Err(ReadError::UnexpectedEof {
+				needed: 3,
+				available: 2
+			})
+		));
+	}
+
+	#[test]
+	fn test_read_list_byte_order() {
+		let bytes: &[u8] = &[1, 0, 2, 0];
+		let mut reader = ByteOrdered::new(bytes, ByteOrder::LittleEndian);
+
+		assert_eq!(Vec::<u16>::read_with(&mut reader, &2).unwrap(), [1, 2]);
+	}
+
+	#[test]
+	fn test_read_list_copied() {
+		let bytes: &[u8] = &[1, 2, 3, 4, 5, 6, 7, 8];
+
+		let mut reader = bytes;
+		assert_eq!(
+			Vec::<u16>::read_with(&mut reader, &4).unwrap(),
+			[0x0102, 0x0304, 0x0506, 0x0708]
+		);
+		assert!(reader.is_empty());
+
+		let mut reader = ByteOrdered::new(bytes, ByteOrder::LittleEndian);
+		assert_eq!(
+			Vec::<u16>::read_with(&mut reader, &4).unwrap(),
+			[0x0201, 0x0403, 0x0605, 0x0807]
+		);
+
+		let mut reader = ByteOrdered::new(
+			&[0, 0, 0x80, 0x3f, 0, 0, 0, 0x40][..],
+			ByteOrder::LittleEndian,
+		);
+		assert_eq!(Vec::<f32>::read_with(&mut reader, &2).unwrap(), [1.0, 2.0]);
+
+		// Values may be split between chunks.
+		let mut reader = Buf::chain(&[1, 2, 3][..], &[4, 5, 6][..]);
+		assert_eq!(
+			Vec::<u16>::read_with(&mut reader, &3).unwrap(),
+			[0x0102, 0x0304, 0x0506]
+		);
+	}
+
+	#[test]
+	fn test_read_list_u8() {
+		let mut bytes: &[u8] = &[1, 2, 3, 4, 5];
+
+		assert_eq!(Vec::<u8>::read_with(&mut bytes, &2).unwrap(), [1, 2]);
+		assert_eq!(<[u8; 2]>::read_from(&mut bytes).unwrap(), [3, 4]);
+		assert_eq!(bytes, [5]);
+
+		assert!(matches!(
+			Vec::<u8>::read_with(&mut bytes, &2),
+			Err(ReadError::InField { ref path, offset: 1, .. }) if path.to_string() == "[1]"
+		));
+		assert!(matches!(
+			<[u8; 2]>::read_from(&mut bytes),
+			Err(ReadError::InField { ref path, offset: 1, .. }) if path.to_string() == "[1]"
+		));
+	}
+
+	#[test]
+	fn test_read_array_copied() {
+		let bytes: &[u8] = &[0, 0, 1, 2, 3, 4];
+
+		let mut reader = ByteOrdered::new(bytes, ByteOrder::LittleEndian);
+		assert_eq!(
+			<[u16; 3]>::read_from(&mut reader).unwrap(),
+			[0x0000, 0x0201, 0x0403]
+		);
+
+		let mut reader = bytes;
+		assert_eq!(
+			<[[u16; 1]; 3]>::read_from(&mut reader).unwrap(),
+			[[0x0000], [0x0102], [0x0304]]
+		);
+		assert_eq!(<[bool; 0]>::read_from(&mut reader).unwrap(), []);
+	}
+
+	#[test]
+	fn test_read_array_drops_partial() {
+		use std::cell::Cell;
+
+		thread_local! {
+			static DROPPED: Cell<usize> = const { Cell::new(0) };
+		}
+
+		struct Counted;
+
+		impl crate::DataSize for Counted {
+			fn data_size(&self) -> usize {
+				1
+			}
+		}
+
+		impl Readable for Counted {
+			fn read_from(reader: &mut impl crate::OrderedBuf) -> crate::ReadResult<Self> {
+				u8::read_from(reader).map(|_| Self)
+			}
 		}
 
-		Ok(vec)
+		impl Drop for Counted {
+			fn drop(&mut self) {
+				DROPPED.with(|dropped| dropped.set(dropped.get() + 1));
+			}
+		}
+
+		let mut bytes: &[u8] = &[1, 2];
+		let error = <[Counted; 4]>::read_from(&mut bytes).err().unwrap();
+		assert!(matches!(
+			error,
+			ReadError::InField { ref path, offset: 2, .. } if path.to_string() == "[2]"
+		));
+		assert_eq!(DROPPED.with(Cell::get), 2);
+
+		let mut bytes: &[u8] = &[1, 2];
+		drop(<[Counted; 2]>::read_from(&mut bytes).unwrap());
+		assert_eq!(DROPPED.with(Cell::get), 4);
 	}
 }
@@ -0,0 +1,202 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use crate::{
+	ensure_capacity, ensure_remaining, ContextualReadable, DataSize, Length, Limits, OrderedBuf,
+	OrderedBufMut, ReadError, ReadResult, Readable, Writable, WriteError, WriteResult,
+};
+
+/// Reads `len` bytes from `reader` as UTF-8.
+fn read_utf8(reader: &mut impl OrderedBuf, len: usize) -> ReadResult<String> {
+	Limits::current().check_list(len, Some(1))?;
+	ensure_remaining(reader, len)?;
+
+	let mut bytes = vec![0; len];
+	reader.copy_to_slice(&mut bytes);
+
+	String::from_utf8(bytes).map_err(|error| ReadError::InvalidUtf8(error.utf8_error()))
+}
+
+/// Reads a UTF-8 string of `context` bytes, within the
+/// [current](Limits::current) limits.
+impl ContextualReadable for String {
+	type Context = usize;
+
+	fn read_with(reader: &mut impl OrderedBuf, context: &Self::Context) -> ReadResult<Self> {
+		read_utf8(reader, *context)
+	}
+}
+
+/// A string which is terminated by a NUL byte, like a C string.
+///
+/// The NUL byte is not included in the [`String`]. A [`NulTerminated`] string
+/// which contains a NUL byte cannot be written. When read, the string is
+/// checked against the [current](Limits::current) limits as it is read.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct NulTerminated(pub String);
+
+impl DataSize for NulTerminated {
+	fn data_size(&self) -> usize {
+		self.0.len() + 1
+	}
+}
+
+impl Readable for NulTerminated {
+	fn read_from(reader: &mut impl OrderedBuf) -> ReadResult<Self> {
+		let limits = Limits::current();
+		let mut bytes = Vec::new();
+
+		loop {
+			ensure_remaining(reader, 1)?;
+
+			match reader.get_u8() {
+				0 => break,
+
+				byte => {
+					// The length isn't known until the NUL byte is found, so
+					// the string is checked as it grows.
+					limits.check_list(bytes.len() + 1, Some(1))?;
+					bytes.push(byte);
+				}
+			}
+		}
+
+		String::from_utf8(bytes)
+			.map(Self)
+			.map_err(|error| ReadError::InvalidUtf8(error.utf8_error()))
+	}
+}
+
+impl Writable for NulTerminated {
+	fn write_to(&self, writer: &mut impl OrderedBufMut) -> WriteResult {
+		if self.0.contains('\0') {
+			return Err(WriteError::Other(
+				"a NUL-terminated string cannot contain a NUL byte".into(),
+			));
+		}
+
+		ensure_capacity(writer, self.data_size())?;
+
+		writer.put_slice(self.0.as_bytes());
+		writer.put_u8(0);
+
+		Ok(())
+	}
+}
+
+/// A Latin-1 (ISO 8859-1) string, such as an X11 `STRING8`.
+///
+/// Every byte is a valid Latin-1 character, so reading a [`Latin1`] string
+/// never fails because of its contents. Like [`String`], it is read with its
+/// length in bytes as its context.
+///
+/// Only characters up to `U+00FF` can be written.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Latin1(pub String);
+
+impl DataSize for Latin1 {
+	fn data_size(&self) -> usize {
+		self.0.chars().count()
+	}
+}
+
+impl ContextualReadable for Latin1 {
+	type Context = usize;
+
+	fn read_with(reader: &mut impl OrderedBuf, context: &Self::Context) -> ReadResult<Self> {
+		Limits::current().check_list(*context, Some(1))?;
+		ensure_remaining(reader, *context)?;
+
+		Ok(Self(
+			(0..*context).map(|_| char::from(reader.get_u8())).collect(),
+		))
+	}
+}
+
+impl Writable for Latin1 {
+	fn write_to(&self, writer: &mut impl OrderedBufMut) -> WriteResult {
+		let bytes = self
+			.0
+			.chars()
+			.map(u8::try_from)
+			.collect::<Result<Vec<_>, _>>()
+			.map_err(|error| WriteError::Other(Box::new(error)))?;
+
+		ensure_capacity(writer, bytes.len())?;
+		writer.put_slice(&bytes);
+
+		Ok(())
+	}
+}
+
+impl Length for Latin1 {
+	fn length(&self) -> usize {
+		self.data_size()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::{Latin1, NulTerminated};
+	use crate::{ContextualReadable, ReadError, Readable, Writable, WriteError};
+
+	#[test]
+	fn test_string_round_trip() {
+		let mut bytes = Vec::new();
+		"héllo".write_to(&mut bytes).unwrap();
+		assert_eq!(bytes, "héllo".as_bytes());
+
+		let string = String::read_with(&mut &bytes[..], &bytes.len()).unwrap();
+		assert_eq!(string, "héllo");
+	}
+
+	#[test]
+	fn test_read_invalid_utf8() {
+		let error = String::read_with(&mut &[0x68, 0xff][..], &2).unwrap_err();
+		assert!(matches!(error, ReadError::InvalidUtf8(_)));
+	}
+
+	#[test]
+	fn test_nul_terminated_round_trip() {
+		let mut bytes = Vec::new();
+		NulTerminated("abc".to_owned())
+			.write_to(&mut bytes)
+			.unwrap();
+		assert_eq!(bytes, b"abc\0");
+
+		let mut reader: &[u8] = b"abc\0def";
+		let string = NulTerminated::read_from(&mut reader).unwrap();
+		assert_eq!(string.0, "abc");
+		assert_eq!(reader, b"def");
+	}
+
+	#[test]
+	fn test_nul_terminated_errors() {
+		let error = NulTerminated::read_from(&mut &b"abc"[..]).unwrap_err();
+		assert!(matches!(error, ReadError::UnexpectedEof { .. }));
+
+		let error = NulTerminated("a\0b".to_owned())
+			.write_to(&mut Vec::new())
+			.unwrap_err();
+		assert!(matches!(error, WriteError::Other(_)));
+	}
+
+	#[test]
+	fn test_latin1_round_trip() {
+		let string = Latin1::read_with(&mut &[0x63, 0x61, 0x66, 0xe9, 0xff][..], &5).unwrap();
+		assert_eq!(string.0, "café\u{ff}");
+
+		let mut bytes = Vec::new();
+		string.write_to(&mut bytes).unwrap();
+		assert_eq!(bytes, [0x63, 0x61, 0x66, 0xe9, 0xff]);
+	}
+
+	#[test]
+	fn test_latin1_unencodable() {
+		let error = Latin1("€".to_owned())
+			.write_to(&mut Vec::new())
+			.unwrap_err();
+		assert!(matches!(error, WriteError::Other(_)));
+	}
+}
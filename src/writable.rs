@@ -2,19 +2,53 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
-use crate::{Writable, WriteResult};
-use bytes::BufMut;
+use crate::number::{self, Number};
+use crate::{DataSize, OrderedBufMut, Writable, WriteError, WriteResult};
+use bytes::{BufMut, Bytes};
+use std::marker::PhantomData;
+use std::mem;
 
-macro_rules! implement {
-	($($ident:ident: &$ty:ty => BufMut::$fun:ident($expr:expr)),*$(,)?) => {
+/// Checks that at least `required` bytes can be written to `writer`.
+///
+/// [`BufMut`]'s methods panic if there is too little capacity remaining, so
+/// this should be called before writing to `writer` directly. Types which
+/// write other [`Writable`] values may call this with their
+/// [`data_size`](DataSize::data_size) first so that nothing is written if
+/// there is not enough room for all of it.
+///
+/// # Errors
+/// Returns [`WriteError::InsufficientCapacity`] if fewer than `required`
+/// bytes can be written.
+pub fn ensure_capacity(writer: &impl BufMut, required: usize) -> WriteResult {
+	let remaining = writer.remaining_mut();
+
+	if remaining < required {
+		return Err(WriteError::InsufficientCapacity {
+			required,
+			remaining,
+		});
+	}
+
+	Ok(())
+}
+
+// Lists of numbers check the capacity remaining once, rather than for each
+// value, and are copied all at once.
+macro_rules! numbers {
+	($($ty:ty),*$(,)?) => {
 		$(
-			impl $crate::Writable for $ty {
-				fn write_to(
-					&self,
-					writer: &mut impl bytes::BufMut,
-				) -> Result<(), $crate::WriteError> {
-					let $ident = self;
-					writer.$fun($expr);
+			impl Writable for $ty {
+				fn write_to(&self, writer: &mut impl OrderedBufMut) -> WriteResult {
+					ensure_capacity(writer, mem::size_of::<Self>())?;
+					self.put(writer);
+
+					Ok(())
+				}
+
+				fn write_list(list: &[Self], writer: &mut impl OrderedBufMut) -> WriteResult {
+					ensure_capacity(writer, mem::size_of_val(list))?;
+
+					number::write_list(list, writer);
 
 					Ok(())
 				}
@@ -23,57 +57,83 @@ macro_rules! implement {
 	};
 }
 
-implement! {
-	n: &i8 => BufMut::put_i8(*n),
-	n: &i16 => BufMut::put_i16(*n),
-	n: &i32 => BufMut::put_i32(*n),
-	n: &i64 => BufMut::put_i64(*n),
-	n: &i128 => BufMut::put_i128(*n),
+numbers!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
 
-	n: &u8 => BufMut::put_u8(*n),
-	n: &u16 => BufMut::put_u16(*n),
-	n: &u32 => BufMut::put_u32(*n),
-	n: &u64 => BufMut::put_u64(*n),
-	n: &u128 => BufMut::put_u128(*n),
+impl Writable for bool {
+	fn write_to(&self, writer: &mut impl OrderedBufMut) -> WriteResult {
+		ensure_capacity(writer, 1)?;
+		writer.put_u8(u8::from(*self));
 
-	n: &f32 => BufMut::put_f32(*n),
-	n: &f64 => BufMut::put_f64(*n),
+		Ok(())
+	}
 
-	b: &bool => BufMut::put_u8(*b as u8),
-}
+	fn write_list(list: &[Self], writer: &mut impl OrderedBufMut) -> WriteResult {
+		ensure_capacity(writer, list.len())?;
 
-impl<T: Writable> Writable for &[T] {
-	fn write_to(&self, writer: &mut impl BufMut) -> WriteResult {
-		for x in *self {
-			x.write_to(writer)?;
+		for value in list {
+			writer.put_u8(u8::from(*value));
 		}
 
 		Ok(())
 	}
 }
 
+impl<T: Writable> Writable for &[T] {
+	fn write_to(&self, writer: &mut impl OrderedBufMut) -> WriteResult {
+		ensure_capacity(writer, self.data_size())?;
+
+		T::write_list(self, writer)
+	}
+}
+
 impl<T: Writable, const N: usize> Writable for [T; N] {
-	fn write_to(&self, writer: &mut impl BufMut) -> WriteResult {
-		for x in self {
-			x.write_to(writer)?;
-		}
+	fn write_to(&self, writer: &mut impl OrderedBufMut) -> WriteResult {
+		ensure_capacity(writer, self.data_size())?;
 
-		Ok(())
+		T::write_list(self, writer)
 	}
 }
 
 impl<T: Writable> Writable for Vec<T> {
-	fn write_to(&self, writer: &mut impl BufMut) -> WriteResult {
-		for x in self {
-			x.write_to(writer)?;
-		}
+	fn write_to(&self, writer: &mut impl OrderedBufMut) -> WriteResult {
+		ensure_capacity(writer, self.data_size())?;
+
+		T::write_list(self, writer)
+	}
+}
+
+impl Writable for str {
+	fn write_to(&self, writer: &mut impl OrderedBufMut) -> WriteResult {
+		ensure_capacity(writer, self.len())?;
+		writer.put_slice(self.as_bytes());
+
+		Ok(())
+	}
+}
+
+impl Writable for &str {
+	fn write_to(&self, writer: &mut impl OrderedBufMut) -> WriteResult {
+		str::write_to(self, writer)
+	}
+}
+
+impl Writable for String {
+	fn write_to(&self, writer: &mut impl OrderedBufMut) -> WriteResult {
+		self.as_str().write_to(writer)
+	}
+}
+
+impl Writable for Bytes {
+	fn write_to(&self, writer: &mut impl OrderedBufMut) -> WriteResult {
+		ensure_capacity(writer, self.len())?;
+		writer.put_slice(self);
 
 		Ok(())
 	}
 }
 
 impl<T: Writable> Writable for &T {
-	fn write_to(&self, writer: &mut impl BufMut) -> WriteResult {
+	fn write_to(&self, writer: &mut impl OrderedBufMut) -> WriteResult {
 		T::write_to(self, writer)?;
 
 		Ok(())
@@ -81,7 +141,7 @@ impl<T: Writable> Writable for &T {
 }
 
 impl<T: Writable> Writable for &mut T {
-	fn write_to(&self, writer: &mut impl BufMut) -> WriteResult {
+	fn write_to(&self, writer: &mut impl OrderedBufMut) -> WriteResult {
 		T::write_to(self, writer)?;
 
 		Ok(())
@@ -89,9 +149,137 @@ impl<T: Writable> Writable for &mut T {
 }
 
 impl<T: Writable> Writable for Box<T> {
-	fn write_to(&self, writer: &mut impl BufMut) -> WriteResult {
+	fn write_to(&self, writer: &mut impl OrderedBufMut) -> WriteResult {
 		T::write_to(self, writer)?;
 
 		Ok(())
 	}
 }
+
+impl Writable for () {
+	fn write_to(&self, _writer: &mut impl OrderedBufMut) -> WriteResult {
+		Ok(())
+	}
+}
+
+macro_rules! tuples {
+	($(($($ty:ident $index:tt),+)),+$(,)?) => {
+		$(
+			impl<$($ty: Writable),+> Writable for ($($ty,)+) {
+				fn write_to(&self, writer: &mut impl OrderedBufMut) -> WriteResult {
+					ensure_capacity(writer, self.data_size())?;
+					$(self.$index.write_to(writer)?;)+
+
+					Ok(())
+				}
+			}
+		)+
+	};
+}
+
+tuples! {
+	(A 0),
+	(A 0, B 1),
+	(A 0, B 1, C 2),
+	(A 0, B 1, C 2, D 3),
+	(A 0, B 1, C 2, D 3, E 4),
+	(A 0, B 1, C 2, D 3, E 4, F 5),
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6),
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7),
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8),
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9),
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10),
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11),
+}
+
+impl<T: ?Sized> Writable for PhantomData<T> {
+	fn write_to(&self, _writer: &mut impl OrderedBufMut) -> WriteResult {
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::{ByteOrder, ByteOrdered, Writable, WriteError};
+
+	#[test]
+	fn test_write_primitive_insufficient_capacity() {
+		let mut buf = [0u8; 3];
+		assert!(matches!(
+			0u32.write_to(&mut &mut buf[..]),
+			Err(WriteError::InsufficientCapacity {
+				required: 4,
+				remaining: 3
+			})
+		));
+	}
+
+	#[test]
+	fn test_write_vec_insufficient_capacity() {
+		let mut buf = [0u8; 5];
+		let mut writer = &mut buf[..];
+		assert!(matches!(
+			vec![1u16, 2, 3].write_to(&mut writer),
+			Err(WriteError::InsufficientCapacity {
+				required: 6,
+				remaining: 5
+			})
+		));
+
+		// Nothing is written if there isn't room for all of it.
+		assert_eq!(writer.len(), 5);
+		assert_eq!(buf, [0; 5]);
+	}
+
+	#[test]
+	fn test_write_array_exact_capacity() {
+		let mut buf = [0u8; 4];
+		[1u16, 2].write_to(&mut &mut buf[..]).unwrap();
+		assert_eq!(buf, [0, 1, 0, 2]);
+	}
+
+	#[test]
+	fn test_write_list_copied() {
+		// More numbers than are converted at once.
+		let list: Vec<u16> = (0..100).map(|n| n << 8 | (n + 1)).collect();
+
+		let mut writer = Vec::new();
+		list.write_to(&mut writer).unwrap();
+		assert_eq!(writer, (0..200).map(|n| n / 2 + n % 2).collect::<Vec<u8>>());
+
+		let mut writer = ByteOrdered::new(Vec::new(), ByteOrder::LittleEndian);
+		list.write_to(&mut writer).unwrap();
+		assert_eq!(
+			writer.into_inner(),
+			(0..200).map(|n| n / 2 + 1 - n % 2).collect::<Vec<u8>>()
+		);
+
+		let mut writer = ByteOrdered::new(Vec::new(), ByteOrder::LittleEndian);
+		[1.0_f32, 2.0].write_to(&mut writer).unwrap();
+		[-1_i8, 2].write_to(&mut writer).unwrap();
+		assert_eq!(
+			writer.into_inner(),
+			[0, 0, 0x80, 0x3f, 0, 0, 0, 0x40, 0xff, 2]
+		);
+	}
+
+	#[test]
+	fn test_write_tuple() {
+		let mut bytes = Vec::new();
+		(1u8, (), 2u16, std::marker::PhantomData::<u64>)
+			.write_to(&mut bytes)
+			.unwrap();
+		assert_eq!(bytes, [1, 0, 2]);
+	}
+
+	#[test]
+	fn test_write_list_byte_order() {
+		let mut writer = ByteOrdered::new(Vec::new(), ByteOrder::LittleEndian);
+		vec![1u16, 2].write_to(&mut writer).unwrap();
+		assert_eq!(writer.into_inner(), [1, 0, 2, 0]);
+
+		let mut bytes = Vec::new();
+		[1u8, 2, 3].write_to(&mut bytes).unwrap();
+		assert_eq!(bytes, [1, 2, 3]);
+	}
+}
@@ -0,0 +1,60 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use cornflakes::{ContextualReadable, Flagged, Prefixed};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Records the size of the largest allocation.
+struct Largest;
+
+static LARGEST: AtomicUsize = AtomicUsize::new(0);
+
+// SAFETY: allocations are passed straight to the system allocator.
+unsafe impl GlobalAlloc for Largest {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		LARGEST.fetch_max(layout.size(), Ordering::Relaxed);
+
+		// SAFETY: the caller upholds `alloc`'s requirements.
+		unsafe { System.alloc(layout) }
+	}
+
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		// SAFETY: the caller upholds `dealloc`'s requirements.
+		unsafe { System.dealloc(ptr, layout) }
+	}
+}
+
+#[global_allocator]
+static ALLOCATOR: Largest = Largest;
+
+/// Returns the size of the largest allocation made by `read`.
+fn largest_allocation(read: impl FnOnce()) -> usize {
+	LARGEST.store(0, Ordering::Relaxed);
+	read();
+
+	LARGEST.load(Ordering::Relaxed)
+}
+
+#[test]
+fn test_list_allocation_is_limited_by_input() {
+	// Each value is much larger in memory than the one byte it takes to encode
+	// `None`, so a capacity of one value per byte would allocate about 4 MB.
+	let bytes = [1; 1000];
+	let size = largest_allocation(|| {
+		let list = Vec::<Flagged<[u8; 4096]>>::read_with(&mut &bytes[..], &usize::MAX);
+		assert!(list.is_err());
+	});
+	assert!(size <= bytes.len(), "allocated {size} bytes");
+
+	// A `Prefixed<u8, String>` is 24 bytes in memory, but only one byte when
+	// it is empty.
+	let mut bytes = [0; 1000];
+	bytes[..2].copy_from_slice(&[1, 0xff]);
+	let size = largest_allocation(|| {
+		let list = Vec::<Prefixed<u8, String>>::read_with(&mut &bytes[..], &usize::MAX);
+		assert!(list.is_err());
+	});
+	assert!(size <= bytes.len(), "allocated {size} bytes");
+}
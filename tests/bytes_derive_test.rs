@@ -0,0 +1,26 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+mod common;
+
+use common::write;
+use cornflakes::derive::{DataSize, Readable, Writable};
+use cornflakes::Readable;
+
+#[derive(Debug, PartialEq, Eq, DataSize, Readable, Writable)]
+struct TestPropertyReply {
+	len: u32,
+	#[context(len as usize)]
+	value: cornflakes::Bytes,
+}
+
+#[test]
+fn test_bytes_field_zero_copy() {
+	let input = cornflakes::Bytes::from_static(&[0, 0, 0, 3, 7, 8, 9]);
+	let data = TestPropertyReply::read_from(&mut input.clone()).unwrap();
+
+	assert_eq!(data.value, [7, 8, 9][..]);
+	assert_eq!(data.value.as_ptr(), input[4..].as_ptr());
+	assert_eq!(write(&data), input);
+}
@@ -0,0 +1,15 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use cornflakes::Writable;
+
+/// Writes `data` to a new buffer, checking that exactly `data_size()` bytes
+/// were written.
+pub fn write<T: Writable>(data: &T) -> Vec<u8> {
+	let mut bytes = Vec::new();
+	data.write_to(&mut bytes).unwrap();
+
+	assert_eq!(bytes.len(), data.data_size());
+	bytes
+}
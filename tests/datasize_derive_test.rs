@@ -3,8 +3,6 @@
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
 #![allow(unused)]
-#![allow(incomplete_features)]
-#![feature(specialization)]
 
 use cornflakes::derive::{DataSize, StaticDataSize};
 
@@ -49,40 +47,85 @@ struct TestDynamicStruct<'a> {
 	s: &'a [u32],
 }
 
+#[derive(DataSize)]
+#[discriminant(u16)]
+enum TestWideDynamicEnum {
+	Unit,
+	Unnamed(Vec<u8>),
+}
+
+#[derive(StaticDataSize)]
+#[discriminant(u32)]
+enum TestWideSizedEnum {
+	Unit,
+	Unnamed(u16),
+}
+
 #[derive(DataSize)]
 struct TestDynamicTuple(Vec<Option<u64>>, i64);
 
-// We don't know if the generic implements DataSize or StaticDataSize,
-// So we need both for both case.
-#[derive(DataSize, StaticDataSize)]
+/// Only deriving `DataSize` doesn't pad variants to the largest one
+#[derive(DataSize)]
+enum TestUnpaddedEnum {
+	Unit,
+	Unnamed(u16),
+}
+
+#[derive(DataSize)]
+enum TestSameSizeEnum {
+	First(u16),
+	Second(i16),
+}
+
+// `StaticDataSize` also derives `DataSize`, so these are only `DataSize` when
+// the generic is `StaticDataSize`.
+#[derive(StaticDataSize)]
 enum TestEnumGenerics<T> {
 	Unit,
 	Unnamed(T),
 	Named { field1: T, field2: T },
 }
 
-#[derive(DataSize, StaticDataSize)]
+#[derive(StaticDataSize)]
 struct TestStructGenerics<'a, T: 'a> {
 	value: &'a T,
 	wrapper: Option<T>,
 	enum_value: TestEnumGenerics<Option<T>>,
 }
 
-#[derive(DataSize, StaticDataSize)]
+#[derive(StaticDataSize)]
 struct TestTupleGenerics<T>(Option<T>, TestEnumGenerics<T>);
 
+// The same types for generics which only implement `DataSize`.
+#[derive(DataSize)]
+enum TestDynamicEnumGenerics<T> {
+	Unit,
+	Unnamed(T),
+	Named { field1: T, field2: T },
+}
+
+#[derive(DataSize)]
+struct TestDynamicStructGenerics<'a, T: 'a> {
+	value: &'a T,
+	wrapper: Option<T>,
+	enum_value: TestDynamicEnumGenerics<Option<T>>,
+}
+
+#[derive(DataSize)]
+struct TestDynamicTupleGenerics<T>(Option<T>, TestDynamicEnumGenerics<T>);
+
 // Tests
 
 #[test]
 fn test_sized_enum_unit() {
 	let data = TestSizedEnum::Unit;
-	assert_eq!(<TestSizedEnum as cornflakes::DataSize>::data_size(&data), 5);
+	assert_eq!(<TestSizedEnum as cornflakes::DataSize>::data_size(&data), 6);
 }
 
 #[test]
 fn test_sized_enum_unnamed() {
 	let data = TestSizedEnum::Unnamed(u16::default());
-	assert_eq!(<TestSizedEnum as cornflakes::DataSize>::data_size(&data), 5);
+	assert_eq!(<TestSizedEnum as cornflakes::DataSize>::data_size(&data), 6);
 }
 
 #[test]
@@ -91,7 +134,7 @@ fn test_sized_enum_named() {
 		field1: u32::default(),
 		field2: i8::default(),
 	};
-	assert_eq!(<TestSizedEnum as cornflakes::DataSize>::data_size(&data), 5);
+	assert_eq!(<TestSizedEnum as cornflakes::DataSize>::data_size(&data), 6);
 }
 
 #[test]
@@ -103,7 +146,7 @@ fn test_sized_struct() {
 	};
 	assert_eq!(
 		<TestSizedStruct as cornflakes::DataSize>::data_size(&data),
-		17
+		18
 	);
 }
 
@@ -130,7 +173,7 @@ fn test_dynamic_enum_unnamed() {
 	let data = TestDynamicEnum::Unnamed(Vec::from([u8::default(), u8::default()]));
 	assert_eq!(
 		<TestDynamicEnum as cornflakes::DataSize>::data_size(&data),
-		2
+		3
 	);
 }
 
@@ -142,7 +185,7 @@ fn test_dynamic_enum_named() {
 	};
 	assert_eq!(
 		<TestDynamicEnum as cornflakes::DataSize>::data_size(&data),
-		24
+		25
 	);
 }
 
@@ -156,7 +199,7 @@ fn test_dynamic_struct() {
 	};
 	assert_eq!(
 		<TestDynamicStruct as cornflakes::DataSize>::data_size(&data),
-		21
+		22
 	);
 }
 
@@ -169,12 +212,36 @@ fn test_dynamic_tuple() {
 	);
 }
 
+#[test]
+fn test_unpadded_enum() {
+	let data = TestUnpaddedEnum::Unit;
+	assert_eq!(
+		<TestUnpaddedEnum as cornflakes::DataSize>::data_size(&data),
+		1
+	);
+
+	let data = TestUnpaddedEnum::Unnamed(u16::default());
+	assert_eq!(
+		<TestUnpaddedEnum as cornflakes::DataSize>::data_size(&data),
+		3
+	);
+
+	assert_eq!(
+		<TestUnpaddedEnum as cornflakes::DataSize>::static_data_size_hint(),
+		None
+	);
+	assert_eq!(
+		<TestSameSizeEnum as cornflakes::DataSize>::static_data_size_hint(),
+		Some(3)
+	);
+}
+
 #[test]
 fn test_enum_with_sized_generics_unit() {
 	let data = TestEnumGenerics::<u32>::Unit;
 	assert_eq!(
 		<TestEnumGenerics<u32> as cornflakes::DataSize>::data_size(&data),
-		8
+		9
 	);
 }
 
@@ -183,7 +250,7 @@ fn test_enum_with_sized_generics_unnamed() {
 	let data = TestEnumGenerics::<u8>::Unnamed(u8::default());
 	assert_eq!(
 		<TestEnumGenerics<u8> as cornflakes::DataSize>::data_size(&data),
-		2
+		3
 	);
 }
 
@@ -195,37 +262,38 @@ fn test_enum_with_sized_generics_named() {
 	};
 	assert_eq!(
 		<TestEnumGenerics<i64> as cornflakes::DataSize>::data_size(&data),
-		16
+		17
 	);
 }
 
 #[test]
 fn test_enum_with_dynamic_generics_unit() {
-	let data = TestEnumGenerics::<Vec<u32>>::Unit;
+	let data = TestDynamicEnumGenerics::<Vec<u32>>::Unit;
 	assert_eq!(
-		<TestEnumGenerics<Vec<u32>> as cornflakes::DataSize>::data_size(&data),
+		<TestDynamicEnumGenerics<Vec<u32>> as cornflakes::DataSize>::data_size(&data),
 		1
 	);
 }
 
 #[test]
 fn test_enum_with_dynamic_generics_unnamed() {
-	let data = TestEnumGenerics::<Vec<u8>>::Unnamed(Vec::from([u8::default(), u8::default()]));
+	let data =
+		TestDynamicEnumGenerics::<Vec<u8>>::Unnamed(Vec::from([u8::default(), u8::default()]));
 	assert_eq!(
-		<TestEnumGenerics<Vec<u8>> as cornflakes::DataSize>::data_size(&data),
-		2
+		<TestDynamicEnumGenerics<Vec<u8>> as cornflakes::DataSize>::data_size(&data),
+		3
 	);
 }
 
 #[test]
 fn test_enum_with_dynamic_generics_named() {
-	let data = TestEnumGenerics::<Vec<i64>>::Named {
+	let data = TestDynamicEnumGenerics::<Vec<i64>>::Named {
 		field1: vec![i64::default()],
 		field2: vec![i64::default(); 10],
 	};
 	assert_eq!(
-		<TestEnumGenerics<Vec<i64>> as cornflakes::DataSize>::data_size(&data),
-		88
+		<TestDynamicEnumGenerics<Vec<i64>> as cornflakes::DataSize>::data_size(&data),
+		89
 	);
 }
 
@@ -238,19 +306,19 @@ fn test_struct_with_sized_generics() {
 	};
 	assert_eq!(
 		<TestStructGenerics<u16> as cornflakes::DataSize>::data_size(&data),
-		8
+		9
 	);
 }
 
 #[test]
 fn test_struct_with_dynamic_generics() {
-	let data = TestStructGenerics::<Vec<u8>> {
+	let data = TestDynamicStructGenerics::<Vec<u8>> {
 		value: &vec![u8::default()],
 		wrapper: Some(vec![u8::default(); 2]),
-		enum_value: TestEnumGenerics::Unit,
+		enum_value: TestDynamicEnumGenerics::Unit,
 	};
 	assert_eq!(
-		<TestStructGenerics<Vec<u8>> as cornflakes::DataSize>::data_size(&data),
+		<TestDynamicStructGenerics<Vec<u8>> as cornflakes::DataSize>::data_size(&data),
 		4
 	);
 }
@@ -260,15 +328,83 @@ fn test_tuple_with_sized_generics() {
 	let data = TestTupleGenerics::<i8>(None, TestEnumGenerics::Unit);
 	assert_eq!(
 		<TestTupleGenerics<i8> as cornflakes::DataSize>::data_size(&data),
-		3
+		4
 	);
 }
 
 #[test]
 fn test_tuple_with_dynamic_generics() {
-	let data = TestTupleGenerics::<Vec<i8>>(Some(vec![i8::default(); 10]), TestEnumGenerics::Unit);
+	let data = TestDynamicTupleGenerics::<Vec<i8>>(
+		Some(vec![i8::default(); 10]),
+		TestDynamicEnumGenerics::Unit,
+	);
 	assert_eq!(
-		<TestTupleGenerics<Vec<i8>> as cornflakes::DataSize>::data_size(&data),
+		<TestDynamicTupleGenerics<Vec<i8>> as cornflakes::DataSize>::data_size(&data),
 		11
 	);
 }
+
+#[test]
+fn test_wide_discriminant_dynamic_enum() {
+	let data = TestWideDynamicEnum::Unit;
+	assert_eq!(
+		<TestWideDynamicEnum as cornflakes::DataSize>::data_size(&data),
+		2
+	);
+
+	let data = TestWideDynamicEnum::Unnamed(vec![u8::default(); 3]);
+	assert_eq!(
+		<TestWideDynamicEnum as cornflakes::DataSize>::data_size(&data),
+		5
+	);
+}
+
+#[test]
+fn test_wide_discriminant_sized_enum() {
+	let data = TestWideSizedEnum::Unit;
+	assert_eq!(
+		<TestWideSizedEnum as cornflakes::DataSize>::data_size(&data),
+		6
+	);
+}
+
+// The sizes of `StaticDataSize` types are known at compile time.
+const _: () = assert!(<TestSizedEnum as cornflakes::StaticDataSize>::SIZE == 6);
+const _: () = assert!(<TestSizedStruct as cornflakes::StaticDataSize>::SIZE == 18);
+
+#[test]
+fn test_static_size_const() {
+	let buffer = [0u8; <TestSizedTuple as cornflakes::StaticDataSize>::SIZE];
+	assert_eq!(buffer.len(), 12);
+
+	assert_eq!(<[TestSizedEnum; 3] as cornflakes::StaticDataSize>::SIZE, 18);
+	assert_eq!(
+		<TestWideSizedEnum as cornflakes::StaticDataSize>::static_data_size(),
+		<TestWideSizedEnum as cornflakes::StaticDataSize>::SIZE
+	);
+}
+
+/// Like an X11 event, which is always 32 bytes.
+#[derive(StaticDataSize)]
+#[static_size = 32]
+enum TestFixedSizeEvent {
+	KeyPress {
+		detail: u8,
+		time: u32,
+		rest: [u8; 26],
+	},
+	Expose([u16; 15]),
+}
+
+#[derive(StaticDataSize)]
+#[static_size = 8]
+struct TestFixedSizeGeneric<T>(T, T);
+
+#[test]
+fn test_static_size_attribute() {
+	assert_eq!(<TestFixedSizeEvent as cornflakes::StaticDataSize>::SIZE, 32);
+	assert_eq!(
+		<TestFixedSizeGeneric<u32> as cornflakes::StaticDataSize>::SIZE,
+		8
+	);
+}
@@ -0,0 +1,39 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use cornflakes::derive::{DataSize, Writable};
+
+trait TestRequest: cornflakes::DynWritable {}
+
+#[derive(DataSize, Writable)]
+struct TestMapWindow {
+	window: u32,
+}
+
+#[derive(DataSize, Writable)]
+struct TestBell(i8);
+
+impl TestRequest for TestMapWindow {}
+impl TestRequest for TestBell {}
+
+#[test]
+fn test_write_dyn_requests() {
+	let requests: Vec<Box<dyn TestRequest>> = vec![
+		Box::new(TestMapWindow { window: 7 }),
+		Box::new(TestBell(-1)),
+	];
+	assert_eq!(
+		requests
+			.iter()
+			.map(|request| request.data_size())
+			.sum::<usize>(),
+		5
+	);
+
+	let mut bytes = Vec::new();
+	for request in &requests {
+		request.write_dyn(&mut bytes).unwrap();
+	}
+	assert_eq!(bytes, [0, 0, 0, 7, 0xff]);
+}
@@ -0,0 +1,61 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+mod common;
+
+use common::write;
+use cornflakes::derive::{Flag, Readable, StaticDataSize, Writable};
+use cornflakes::{ReadError, Readable};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Flag)]
+#[bitmask(u16, strict)]
+enum TestModifierFlag {
+	Shift = 0x0001,
+	Lock = 0x0002,
+	Control = 0x0004,
+	Any = 0x8000,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Flag)]
+#[bitmask(u32)]
+enum TestEventFlag {
+	KeyPress = 1 << 0,
+	KeyRelease = 1 << 1,
+}
+
+#[derive(Debug, PartialEq, Eq, StaticDataSize, Readable, Writable)]
+struct TestMasks {
+	modifiers: cornflakes::Bitmask<TestModifierFlag>,
+	events: cornflakes::Bitmask<TestEventFlag>,
+}
+
+#[test]
+fn test_bitmask_round_trip() {
+	let data = TestMasks {
+		modifiers: TestModifierFlag::Shift | TestModifierFlag::Any,
+		events: TestEventFlag::KeyRelease.into(),
+	};
+	assert_eq!(<TestMasks as cornflakes::StaticDataSize>::SIZE, 6);
+
+	let bytes = write(&data);
+	assert_eq!(bytes, [0x80, 0x01, 0, 0, 0, 0x02]);
+	assert_eq!(TestMasks::read_from(&mut &bytes[..]).unwrap(), data);
+}
+
+#[test]
+fn test_bitmask_reserved_bits() {
+	// The event mask isn't strict, so its reserved bits are kept.
+	let mut bytes: &[u8] = &[0, 0x01, 0xff, 0, 0, 0x01];
+	let data = TestMasks::read_from(&mut bytes).unwrap();
+	assert_eq!(data.events.reserved_bits(), 0xff00_0000);
+	assert!(data.events.contains(TestEventFlag::KeyPress));
+
+	// The modifier mask is strict.
+	let mut bytes: &[u8] = &[0, 0x09, 0, 0, 0, 0];
+	let error = TestMasks::read_from(&mut bytes).unwrap_err();
+	assert!(matches!(
+		error.root_cause(),
+		ReadError::ReservedBitsSet { bits: 0x08 }
+	));
+}
@@ -0,0 +1,129 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use cornflakes::derive::{DataSize, Readable};
+use cornflakes::{Limits, NulTerminated, Prefixed, ReadError, Readable};
+
+#[derive(Debug, PartialEq, Eq, DataSize, Readable)]
+struct TestMessage {
+	count: u16,
+	#[context(count as usize)]
+	values: Vec<u32>,
+	name: Prefixed<u8, String>,
+}
+
+#[test]
+fn test_limits_derived_length() {
+	// A `count` of 0xffff is rejected before anything is allocated for it.
+	let mut reader: &[u8] = &[0xff, 0xff, 0, 0, 0, 1];
+	let error = Limits::new(16, usize::MAX)
+		.read_from::<TestMessage>(&mut reader)
+		.unwrap_err();
+
+	assert_eq!(
+		error.to_string(),
+		"failed to read `TestMessage.values` at byte 2: list length 65535 exceeds the maximum \
+		 length of 16"
+	);
+	assert_eq!(reader.len(), 4);
+}
+
+#[test]
+fn test_limits_derived_size() {
+	let bytes: &[u8] = &[0, 1, 0, 0, 0, 1, 3, b'a', b'b', b'c'];
+
+	let mut reader = bytes;
+	let error = Limits::new(16, 2)
+		.read_from::<TestMessage>(&mut reader)
+		.unwrap_err();
+	assert!(matches!(
+		error.root_cause(),
+		ReadError::SizeLimitExceeded { max: 2 }
+	));
+
+	// The `Prefixed` string is a list too.
+	let mut reader: &[u8] = &[0, 0, 3, b'a', b'b', b'c'];
+	let error = Limits::new(16, 2)
+		.read_from::<TestMessage>(&mut reader)
+		.unwrap_err();
+	assert_eq!(
+		error.to_string(),
+		"failed to read `TestMessage.name` at byte 2: list is larger than the maximum size of 2 \
+		 bytes"
+	);
+
+	let mut reader = bytes;
+	let message = Limits::new(16, 8)
+		.read_from::<TestMessage>(&mut reader)
+		.unwrap();
+	assert_eq!(message.values, [1]);
+	assert_eq!(message.name.as_str(), "abc");
+}
+
+#[test]
+fn test_limits_unlimited_outside_read() {
+	let mut reader: &[u8] = &[0xff, 0xff];
+	let _ = Limits::new(1, 1).read_from::<TestMessage>(&mut reader);
+
+	assert_eq!(Limits::current(), Limits::UNLIMITED);
+
+	// Without limits, the list is only limited by the input.
+	let mut reader: &[u8] = &[0, 3, 0, 0, 0, 1];
+	assert!(matches!(
+		TestMessage::read_from(&mut reader)
+			.unwrap_err()
+			.root_cause(),
+		ReadError::UnexpectedEof { .. }
+	));
+}
+
+#[test]
+fn test_limits_nul_terminated() {
+	// The string is rejected as soon as it is too long, without reading the
+	// rest of it.
+	let mut reader: &[u8] = b"abcdef\0";
+	assert!(matches!(
+		Limits::new(4, usize::MAX).read_from::<NulTerminated>(&mut reader),
+		Err(ReadError::LengthLimitExceeded { length: 5, max: 4 }),
+	));
+	assert_eq!(reader, b"f\0");
+
+	let mut reader: &[u8] = b"abcdef\0";
+	assert!(matches!(
+		Limits::new(usize::MAX, 2).read_from::<NulTerminated>(&mut reader),
+		Err(ReadError::SizeLimitExceeded { max: 2 }),
+	));
+
+	let mut reader: &[u8] = b"abcd\0";
+	let string = Limits::new(4, 4)
+		.read_from::<NulTerminated>(&mut reader)
+		.unwrap();
+	assert_eq!(string.0, "abcd");
+}
+
+#[test]
+fn test_limits_zero_sized() {
+	// Reading `()` doesn't consume the input, so billions of them are
+	// rejected by the size limit as though each were a byte.
+	let mut reader: &[u8] = &[];
+	assert!(matches!(
+		Limits::new(usize::MAX, 1024).read_list::<()>(&mut reader, 4_000_000_000),
+		Err(ReadError::SizeLimitExceeded { max: 1024 }),
+	));
+	assert!(matches!(
+		Limits::new(1024, usize::MAX).read_list::<()>(&mut reader, 4_000_000_000),
+		Err(ReadError::LengthLimitExceeded {
+			length: 4_000_000_000,
+			max: 1024
+		}),
+	));
+
+	assert_eq!(
+		Limits::new(1024, 1024)
+			.read_list::<()>(&mut reader, 1024)
+			.unwrap()
+			.len(),
+		1024
+	);
+}
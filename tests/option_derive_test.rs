@@ -0,0 +1,36 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+mod common;
+
+use common::write;
+use cornflakes::derive::{DataSize, Readable, Writable};
+use cornflakes::Readable;
+
+#[derive(Debug, PartialEq, Eq, DataSize, Readable, Writable)]
+struct TestOptions {
+	flagged: cornflakes::Flagged<u16>,
+	trailing: cornflakes::Omittable<u8>,
+}
+
+#[test]
+fn test_options_round_trip() {
+	let data = TestOptions {
+		flagged: Some(3).into(),
+		trailing: None.into(),
+	};
+
+	let bytes = write(&data);
+	assert_eq!(bytes, [1, 0, 3]);
+	assert_eq!(TestOptions::read_from(&mut &bytes[..]).unwrap(), data);
+
+	let data = TestOptions {
+		flagged: None.into(),
+		trailing: Some(7).into(),
+	};
+
+	let bytes = write(&data);
+	assert_eq!(bytes, [0, 7]);
+	assert_eq!(TestOptions::read_from(&mut &bytes[..]).unwrap(), data);
+}
@@ -0,0 +1,27 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+mod common;
+
+use common::write;
+use cornflakes::derive::{DataSize, Readable, Writable};
+use cornflakes::Readable;
+
+#[derive(Debug, PartialEq, Eq, DataSize, Readable, Writable)]
+struct TestPrefixedList {
+	atoms: cornflakes::Prefixed<u16, Vec<u32>>,
+	flag: bool,
+}
+
+#[test]
+fn test_prefixed_list_round_trip() {
+	let data = TestPrefixedList {
+		atoms: vec![1, 2].into(),
+		flag: true,
+	};
+
+	let bytes = write(&data);
+	assert_eq!(bytes, [0, 2, 0, 0, 0, 1, 0, 0, 0, 2, 1]);
+	assert_eq!(TestPrefixedList::read_from(&mut &bytes[..]).unwrap(), data);
+}
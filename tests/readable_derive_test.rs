@@ -0,0 +1,310 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+#![allow(unused)]
+
+use cornflakes::derive::{ContextualReadable, DataSize, Readable};
+use cornflakes::{ByteOrder, ByteOrdered, ContextualReadable, Discriminant, ReadError, Readable};
+
+#[derive(Debug, PartialEq, Eq, DataSize, Readable)]
+struct TestNamedStruct {
+	first: u8,
+	second: u16,
+	third: [u8; 2],
+}
+
+#[derive(Debug, PartialEq, Eq, DataSize, Readable)]
+struct TestTupleStruct(u16, i8);
+
+#[derive(Debug, PartialEq, Eq, DataSize, Readable)]
+struct TestUnitStruct;
+
+#[derive(Debug, PartialEq, Eq, DataSize, Readable)]
+enum TestEnum {
+	Unit,
+	Unnamed(u16),
+	Named { field1: u8, field2: i8 },
+}
+
+#[derive(Debug, PartialEq, Eq, DataSize, Readable)]
+#[discriminant(u16)]
+enum TestWideEnum {
+	Unit,
+	Unnamed(u8),
+}
+
+#[derive(Debug, PartialEq, Eq, DataSize, Readable)]
+struct TestGenerics<T> {
+	value: T,
+	nested: TestTupleStruct,
+}
+
+#[derive(Debug, PartialEq, Eq, DataSize, Readable)]
+struct TestFieldContext {
+	count: u8,
+	#[context(count as usize)]
+	values: Vec<u16>,
+	#[context(values.len() * 2)]
+	doubled: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq, Eq, DataSize, Readable)]
+struct TestTupleFieldContext(u8, #[context(field0 as usize)] Vec<u8>);
+
+#[derive(Debug, PartialEq, Eq, DataSize, Readable)]
+enum TestEnumFieldContext {
+	Empty,
+	List {
+		len: u8,
+		#[context(len as usize)]
+		list: Vec<i8>,
+	},
+}
+
+#[derive(Debug, PartialEq, Eq, DataSize, ContextualReadable)]
+#[context(len: usize)]
+struct TestContainerContext {
+	header: u8,
+	#[context(*len - 1)]
+	body: Vec<u8>,
+}
+
+/// Only `ContextualReadable` is derived, so it must register `#[sentinel]`.
+#[derive(Debug, PartialEq, Eq, ContextualReadable)]
+#[context(_context: ())]
+enum TestContextualSentinel {
+	#[sentinel(0)]
+	None,
+	Some(u16),
+}
+
+impl cornflakes::DataSize for TestContextualSentinel {
+	fn data_size(&self) -> usize {
+		2
+	}
+}
+
+// Tests
+
+#[test]
+fn test_read_named_struct() {
+	let mut bytes: &[u8] = &[1, 0, 2, 3, 4];
+	assert_eq!(
+		TestNamedStruct::read_from(&mut bytes).unwrap(),
+		TestNamedStruct {
+			first: 1,
+			second: 2,
+			third: [3, 4],
+		}
+	);
+	assert!(bytes.is_empty());
+}
+
+#[test]
+fn test_read_tuple_struct() {
+	let mut bytes: &[u8] = &[1, 0, 0xff];
+	assert_eq!(
+		TestTupleStruct::read_from(&mut bytes).unwrap(),
+		TestTupleStruct(256, -1)
+	);
+}
+
+#[test]
+fn test_read_unit_struct() {
+	let mut bytes: &[u8] = &[1];
+	assert_eq!(
+		TestUnitStruct::read_from(&mut bytes).unwrap(),
+		TestUnitStruct
+	);
+	assert_eq!(bytes, &[1]);
+}
+
+#[test]
+fn test_read_enum_unit() {
+	let mut bytes: &[u8] = &[0];
+	assert_eq!(TestEnum::read_from(&mut bytes).unwrap(), TestEnum::Unit);
+}
+
+#[test]
+fn test_read_enum_unnamed() {
+	let mut bytes: &[u8] = &[1, 0, 5];
+	assert_eq!(
+		TestEnum::read_from(&mut bytes).unwrap(),
+		TestEnum::Unnamed(5)
+	);
+}
+
+#[test]
+fn test_read_enum_named() {
+	let mut bytes: &[u8] = &[2, 7, 0xfe];
+	assert_eq!(
+		TestEnum::read_from(&mut bytes).unwrap(),
+		TestEnum::Named {
+			field1: 7,
+			field2: -2
+		}
+	);
+}
+
+#[test]
+fn test_read_enum_unrecognized_discriminant() {
+	let mut bytes: &[u8] = &[3];
+	assert!(matches!(
+		TestEnum::read_from(&mut bytes),
+		Err(ReadError::UnrecognizedDiscriminant(Discriminant::U8(3)))
+	));
+}
+
+#[test]
+fn test_read_wide_discriminant() {
+	let mut bytes: &[u8] = &[0, 1, 5];
+	assert_eq!(
+		TestWideEnum::read_from(&mut bytes).unwrap(),
+		TestWideEnum::Unnamed(5)
+	);
+
+	let mut bytes: &[u8] = &[1, 0];
+	assert!(matches!(
+		TestWideEnum::read_from(&mut bytes),
+		Err(ReadError::UnrecognizedDiscriminant(Discriminant::U16(256)))
+	));
+}
+
+#[test]
+fn test_read_generics() {
+	let mut bytes: &[u8] = &[0, 0, 0, 9, 0, 1, 2];
+	assert_eq!(
+		TestGenerics::<u32>::read_from(&mut bytes).unwrap(),
+		TestGenerics {
+			value: 9,
+			nested: TestTupleStruct(1, 2),
+		}
+	);
+}
+
+#[test]
+fn test_read_field_context() {
+	let mut bytes: &[u8] = &[2, 0, 1, 0, 2, 5, 6, 7, 8, 9];
+	assert_eq!(
+		TestFieldContext::read_from(&mut bytes).unwrap(),
+		TestFieldContext {
+			count: 2,
+			values: vec![1, 2],
+			doubled: vec![5, 6, 7, 8],
+		}
+	);
+	assert_eq!(bytes, &[9]);
+}
+
+#[test]
+fn test_read_tuple_field_context() {
+	let mut bytes: &[u8] = &[3, 4, 5, 6];
+	assert_eq!(
+		TestTupleFieldContext::read_from(&mut bytes).unwrap(),
+		TestTupleFieldContext(3, vec![4, 5, 6])
+	);
+}
+
+#[test]
+fn test_read_enum_field_context() {
+	let mut bytes: &[u8] = &[1, 2, 0xff, 1];
+	assert_eq!(
+		TestEnumFieldContext::read_from(&mut bytes).unwrap(),
+		TestEnumFieldContext::List {
+			len: 2,
+			list: vec![-1, 1],
+		}
+	);
+}
+
+#[test]
+fn test_read_container_context() {
+	let mut bytes: &[u8] = &[1, 2, 3, 4];
+	assert_eq!(
+		TestContainerContext::read_with(&mut bytes, &3).unwrap(),
+		TestContainerContext {
+			header: 1,
+			body: vec![2, 3],
+		}
+	);
+	assert_eq!(bytes, &[4]);
+}
+
+#[test]
+fn test_read_contextual_sentinel() {
+	let mut bytes: &[u8] = &[0, 0, 0, 5];
+	assert_eq!(
+		TestContextualSentinel::read_with(&mut bytes, &()).unwrap(),
+		TestContextualSentinel::None
+	);
+	assert_eq!(
+		TestContextualSentinel::read_with(&mut bytes, &()).unwrap(),
+		TestContextualSentinel::Some(5)
+	);
+}
+
+#[test]
+fn test_read_little_endian() {
+	let mut reader = ByteOrdered::new(&[2, 1, 0, 2, 0, 3, 0, 0, 0][..], ByteOrder::LittleEndian);
+	assert_eq!(
+		TestFieldContext::read_from(&mut reader).unwrap(),
+		TestFieldContext {
+			count: 2,
+			values: vec![1, 2],
+			doubled: vec![3, 0, 0, 0],
+		}
+	);
+}
+
+#[test]
+fn test_read_eof() {
+	let mut bytes: &[u8] = &[3, 0, 1, 0];
+	let error = TestFieldContext::read_from(&mut bytes).unwrap_err();
+
+	assert!(matches!(
+		error.root_cause(),
+		ReadError::UnexpectedEof {
+			needed: 2,
+			available: 1
+		}
+	));
+}
+
+#[derive(Debug, DataSize, Readable)]
+struct TestNestedPath {
+	header: u16,
+	count: u8,
+	#[context(count as usize)]
+	values: Vec<TestEnum>,
+}
+
+#[test]
+fn test_read_error_path() {
+	let mut bytes: &[u8] = &[0, 0, 3, 0, 1, 0, 1, 4];
+	let error = TestNestedPath::read_from(&mut bytes).unwrap_err();
+
+	assert!(matches!(
+		error.root_cause(),
+		ReadError::UnrecognizedDiscriminant(Discriminant::U8(4))
+	));
+	match error {
+		ReadError::InField { path, offset, .. } => {
+			assert_eq!(path.to_string(), "TestNestedPath.values[2]");
+			assert_eq!(offset, 7);
+		}
+		error => panic!("expected `ReadError::InField`, got {error:?}"),
+	}
+}
+
+#[test]
+fn test_read_error_path_enum_field() {
+	let mut bytes: &[u8] = &[2, 7];
+	let error = TestEnum::read_from(&mut bytes).unwrap_err();
+
+	assert_eq!(
+		error.to_string(),
+		"failed to read `TestEnum::Named.field2` at byte 2: \
+		 unexpected end of input: needed 1 bytes, but only 0 remain"
+	);
+}
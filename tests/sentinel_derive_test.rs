@@ -0,0 +1,67 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+mod common;
+
+use common::write;
+use cornflakes::derive::{Readable, Sentinel, StaticDataSize, Wrapper, Writable};
+use cornflakes::Readable;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Wrapper, Sentinel)]
+#[sentinel(0)]
+struct TestWindow(u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, StaticDataSize, Readable, Writable, Sentinel)]
+#[sentinel(0x8000)]
+struct TestModifiers {
+	mask: u16,
+}
+
+#[derive(Debug, PartialEq, Eq, StaticDataSize, Readable, Writable)]
+enum TestFocus {
+	#[sentinel(TestWindow(0))]
+	None,
+	#[sentinel(TestWindow(1))]
+	PointerRoot,
+	Window(TestWindow),
+}
+
+#[derive(Debug, PartialEq, Eq, StaticDataSize, Readable, Writable)]
+struct TestSentinels {
+	parent: Option<TestWindow>,
+	modifiers: Option<TestModifiers>,
+	focus: TestFocus,
+}
+
+#[test]
+fn test_sentinels_round_trip() {
+	assert_eq!(<TestSentinels as cornflakes::StaticDataSize>::SIZE, 10);
+
+	let data = TestSentinels {
+		parent: None,
+		modifiers: None,
+		focus: TestFocus::PointerRoot,
+	};
+
+	let bytes = write(&data);
+	assert_eq!(bytes, [0, 0, 0, 0, 0x80, 0, 0, 0, 0, 1]);
+	assert_eq!(TestSentinels::read_from(&mut &bytes[..]).unwrap(), data);
+
+	let data = TestSentinels {
+		parent: Some(TestWindow(7)),
+		modifiers: Some(TestModifiers { mask: 0 }),
+		focus: TestFocus::Window(TestWindow(9)),
+	};
+
+	let bytes = write(&data);
+	assert_eq!(bytes, [0, 0, 0, 7, 0, 0, 0, 0, 0, 9]);
+	assert_eq!(TestSentinels::read_from(&mut &bytes[..]).unwrap(), data);
+
+	let bytes = write(&TestFocus::None);
+	assert_eq!(bytes, [0, 0, 0, 0]);
+	assert_eq!(
+		TestFocus::read_from(&mut &bytes[..]).unwrap(),
+		TestFocus::None
+	);
+}
@@ -0,0 +1,50 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+mod common;
+
+use common::write;
+use cornflakes::derive::{DataSize, Readable, Writable};
+use cornflakes::{ReadError, Readable};
+
+#[derive(Debug, PartialEq, Eq, DataSize, Readable, Writable)]
+struct TestStrings {
+	len: u8,
+	#[context(len as usize)]
+	raw: String,
+	prefixed: cornflakes::Prefixed<u8, String>,
+	terminated: cornflakes::NulTerminated,
+	#[context(2)]
+	latin1: cornflakes::Latin1,
+}
+
+#[test]
+fn test_strings_round_trip() {
+	let data = TestStrings {
+		len: 2,
+		raw: "hi".to_owned(),
+		prefixed: "abc".to_owned().into(),
+		terminated: cornflakes::NulTerminated("xy".to_owned()),
+		latin1: cornflakes::Latin1("é!".to_owned()),
+	};
+
+	let bytes = write(&data);
+	assert_eq!(
+		bytes,
+		[2, b'h', b'i', 3, b'a', b'b', b'c', b'x', b'y', 0, 0xe9, b'!']
+	);
+	assert_eq!(TestStrings::read_from(&mut &bytes[..]).unwrap(), data);
+}
+
+#[test]
+fn test_read_invalid_utf8_path() {
+	let mut bytes: &[u8] = &[1, 0xff];
+	let error = TestStrings::read_from(&mut bytes).unwrap_err();
+
+	assert_eq!(
+		error.to_string().split(':').next(),
+		Some("failed to read `TestStrings.raw` at byte 1")
+	);
+	assert!(matches!(error.root_cause(), ReadError::InvalidUtf8(_)));
+}
@@ -0,0 +1,30 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+mod common;
+
+use common::write;
+use cornflakes::derive::{Readable, StaticDataSize, Writable};
+use cornflakes::Readable;
+
+#[derive(Debug, PartialEq, Eq, StaticDataSize, Readable, Writable)]
+struct TestTupleFields {
+	point: (i16, i16),
+	unit: (),
+	marker: std::marker::PhantomData<String>,
+}
+
+#[test]
+fn test_tuple_fields_round_trip() {
+	let data = TestTupleFields {
+		point: (1, -1),
+		unit: (),
+		marker: std::marker::PhantomData,
+	};
+	assert_eq!(<TestTupleFields as cornflakes::StaticDataSize>::SIZE, 4);
+
+	let bytes = write(&data);
+	assert_eq!(bytes, [0, 1, 0xff, 0xff]);
+	assert_eq!(TestTupleFields::read_from(&mut &bytes[..]).unwrap(), data);
+}
@@ -0,0 +1,27 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+mod common;
+
+use common::write;
+use cornflakes::derive::Wrapper;
+use cornflakes::Readable;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Wrapper)]
+struct TestAtom(u32);
+
+#[test]
+fn test_wrapper_round_trip() {
+	use cornflakes::Wrapper;
+
+	assert_eq!(<TestAtom as cornflakes::StaticDataSize>::SIZE, 4);
+	assert_eq!(TestAtom::wrap(5).unwrap(), &5);
+
+	let bytes = write(&TestAtom(0x0102_0304));
+	assert_eq!(bytes, [1, 2, 3, 4]);
+	assert_eq!(
+		TestAtom::read_from(&mut &bytes[..]).unwrap(),
+		TestAtom(0x0102_0304)
+	);
+}
@@ -0,0 +1,269 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+mod common;
+
+use common::write;
+use cornflakes::derive::{DataSize, Readable, StaticDataSize, Writable};
+use cornflakes::{DataSize, Discriminant, ReadError, Readable, Writable, WriteError};
+
+#[derive(Debug, PartialEq, Eq, DataSize, Writable)]
+struct TestNamedStruct {
+	first: u8,
+	second: u16,
+	third: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq, Eq, DataSize, Writable)]
+struct TestTupleStruct(u16, i8);
+
+#[derive(Debug, PartialEq, Eq, DataSize, Writable)]
+struct TestUnitStruct;
+
+#[derive(Debug, PartialEq, Eq, DataSize, Writable)]
+enum TestDynamicEnum {
+	Unit,
+	Unnamed(Vec<u8>),
+	Named { field1: u8, field2: i8 },
+}
+
+#[derive(Debug, PartialEq, Eq, StaticDataSize, Readable, Writable)]
+enum TestSizedEnum {
+	Unit,
+	Unnamed(u16),
+	Named { field1: u32, field2: i8 },
+}
+
+#[derive(Debug, PartialEq, Eq, StaticDataSize, Readable, Writable)]
+#[discriminant(u32)]
+enum TestWideEnum {
+	Unit,
+	Unnamed(u16),
+}
+
+#[derive(Debug, PartialEq, Eq, DataSize, Readable, Writable)]
+enum TestExplicitDiscriminants {
+	KeyPress = 2,
+	KeyRelease,
+	ClientMessage = 33,
+	MappingNotify,
+}
+
+#[derive(Debug, PartialEq, Eq, DataSize, Readable, Writable)]
+#[discriminant(u16)]
+enum TestAttributeDiscriminants {
+	Unit,
+	#[discriminant = 0x8000]
+	Unnamed(u8),
+	Named {
+		field: u8,
+	},
+}
+
+#[derive(Debug, PartialEq, Eq, DataSize, Writable)]
+struct TestGenerics<T> {
+	value: T,
+	nested: TestTupleStruct,
+}
+
+// Tests
+
+#[test]
+fn test_write_named_struct() {
+	let data = TestNamedStruct {
+		first: 1,
+		second: 2,
+		third: vec![3, 4, 5],
+	};
+	assert_eq!(write(&data), [1, 0, 2, 3, 4, 5]);
+}
+
+#[test]
+fn test_write_tuple_struct() {
+	assert_eq!(write(&TestTupleStruct(256, -1)), [1, 0, 0xff]);
+}
+
+#[test]
+fn test_write_unit_struct() {
+	assert!(write(&TestUnitStruct).is_empty());
+}
+
+#[test]
+fn test_write_dynamic_enum_unit() {
+	assert_eq!(write(&TestDynamicEnum::Unit), [0]);
+}
+
+#[test]
+fn test_write_dynamic_enum_unnamed() {
+	assert_eq!(write(&TestDynamicEnum::Unnamed(vec![5, 6])), [1, 5, 6]);
+}
+
+#[test]
+fn test_write_dynamic_enum_named() {
+	let data = TestDynamicEnum::Named {
+		field1: 7,
+		field2: -2,
+	};
+	assert_eq!(write(&data), [2, 7, 0xfe]);
+}
+
+#[test]
+fn test_write_sized_enum_is_padded() {
+	assert_eq!(write(&TestSizedEnum::Unit), [0, 0, 0, 0, 0, 0]);
+	assert_eq!(write(&TestSizedEnum::Unnamed(3)), [1, 0, 3, 0, 0, 0]);
+}
+
+#[test]
+fn test_sized_enum_round_trip() {
+	let data = TestSizedEnum::Named {
+		field1: 0x0102_0304,
+		field2: -1,
+	};
+	let bytes = write(&data);
+	assert_eq!(bytes, [2, 1, 2, 3, 4, 0xff]);
+
+	let mut bytes = &bytes[..];
+	assert_eq!(TestSizedEnum::read_from(&mut bytes).unwrap(), data);
+	assert!(bytes.is_empty());
+
+	let mut bytes: &[u8] = &[1, 0, 3, 0, 0, 0, 9];
+	assert_eq!(
+		TestSizedEnum::read_from(&mut bytes).unwrap(),
+		TestSizedEnum::Unnamed(3)
+	);
+	assert_eq!(bytes, [9]);
+}
+
+#[test]
+fn test_read_sized_enum_missing_padding() {
+	let mut bytes: &[u8] = &[1, 0, 3, 0];
+	assert!(matches!(
+		TestSizedEnum::read_from(&mut bytes),
+		Err(ReadError::UnexpectedEof {
+			needed: 3,
+			available: 1
+		})
+	));
+}
+
+#[test]
+fn test_wide_discriminant_round_trip() {
+	assert_eq!(write(&TestWideEnum::Unit), [0, 0, 0, 0, 0, 0]);
+
+	let bytes = write(&TestWideEnum::Unnamed(7));
+	assert_eq!(bytes, [0, 0, 0, 1, 0, 7]);
+	assert_eq!(
+		TestWideEnum::read_from(&mut &bytes[..]).unwrap(),
+		TestWideEnum::Unnamed(7)
+	);
+}
+
+#[test]
+fn test_explicit_discriminants_round_trip() {
+	assert_eq!(write(&TestExplicitDiscriminants::KeyPress), [2]);
+	assert_eq!(write(&TestExplicitDiscriminants::KeyRelease), [3]);
+	assert_eq!(write(&TestExplicitDiscriminants::ClientMessage), [33]);
+	assert_eq!(write(&TestExplicitDiscriminants::MappingNotify), [34]);
+
+	assert_eq!(
+		TestExplicitDiscriminants::read_from(&mut &[34][..]).unwrap(),
+		TestExplicitDiscriminants::MappingNotify
+	);
+	assert!(matches!(
+		TestExplicitDiscriminants::read_from(&mut &[0][..]),
+		Err(ReadError::UnrecognizedDiscriminant(Discriminant::U8(0)))
+	));
+}
+
+#[test]
+fn test_attribute_discriminants_round_trip() {
+	assert_eq!(write(&TestAttributeDiscriminants::Unit), [0, 0]);
+	assert_eq!(write(&TestAttributeDiscriminants::Unnamed(1)), [0x80, 0, 1]);
+	assert_eq!(
+		write(&TestAttributeDiscriminants::Named { field: 2 }),
+		[0x80, 1, 2]
+	);
+
+	assert_eq!(
+		TestAttributeDiscriminants::read_from(&mut &[0x80, 0, 1][..]).unwrap(),
+		TestAttributeDiscriminants::Unnamed(1)
+	);
+}
+
+#[test]
+fn test_write_insufficient_capacity() {
+	let mut buf = [0u8; 5];
+	let mut writer = &mut buf[..];
+	assert!(matches!(
+		TestSizedEnum::Unit.write_to(&mut writer),
+		Err(WriteError::InsufficientCapacity {
+			required: 6,
+			remaining: 5
+		})
+	));
+	assert_eq!(buf, [0; 5]);
+}
+
+#[test]
+fn test_write_generics() {
+	let data = TestGenerics {
+		value: 9u32,
+		nested: TestTupleStruct(1, 2),
+	};
+	assert_eq!(write(&data), [0, 0, 0, 9, 0, 1, 2]);
+}
+
+#[derive(Debug, PartialEq, Eq, DataSize, Readable, Writable)]
+struct TestPadding {
+	len: u8,
+	#[pad(align = 4)]
+	#[context(len as usize)]
+	name: Vec<u8>,
+	#[pad(bytes = 2)]
+	value: u16,
+	pad: cornflakes::Pad<1>,
+}
+
+#[derive(Debug, PartialEq, Eq, StaticDataSize, Readable, Writable)]
+struct TestStaticPadding(#[pad(align = 4)] u8, #[pad(bytes = 3)] u16);
+
+#[test]
+fn test_padding_round_trip() {
+	let data = TestPadding {
+		len: 5,
+		name: vec![1, 2, 3, 4, 5],
+		value: 0x0607,
+		pad: cornflakes::Pad,
+	};
+	assert_eq!(data.data_size(), 1 + 5 + 3 + 2 + 2 + 1);
+
+	let bytes = write(&data);
+	assert_eq!(bytes, [5, 1, 2, 3, 4, 5, 0, 0, 0, 6, 7, 0, 0, 0]);
+	assert_eq!(TestPadding::read_from(&mut &bytes[..]).unwrap(), data);
+}
+
+#[test]
+fn test_static_padding() {
+	assert_eq!(
+		<TestStaticPadding as cornflakes::StaticDataSize>::static_data_size(),
+		4 + 5
+	);
+
+	let bytes = write(&TestStaticPadding(1, 0x0203));
+	assert_eq!(bytes, [1, 0, 0, 0, 2, 3, 0, 0, 0]);
+}
+
+#[test]
+fn test_read_missing_padding() {
+	let mut bytes: &[u8] = &[1, 9, 0];
+	let error = TestPadding::read_from(&mut bytes).unwrap_err();
+
+	assert!(matches!(
+		error.root_cause(),
+		ReadError::UnexpectedEof {
+			needed: 3,
+			available: 1
+		}
+	));
+}